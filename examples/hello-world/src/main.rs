@@ -1,43 +1,45 @@
 use germterm::{
+    app::App,
     crossterm::event::{Event, KeyCode, KeyEvent},
     draw::{draw_fps_counter, draw_text},
-    engine::{Engine, end_frame, exit_cleanup, init, start_frame},
-    input::poll_input,
-    layer::create_layer,
+    engine::{Engine, run},
+    layer::{LayerIndex, create_layer},
 };
 use std::io;
 
-fn main() -> io::Result<()> {
-    let mut engine = Engine::new(40, 20);
-    let layer = create_layer(&mut engine, 0);
-
-    // Initialize engine and layers
-    init(&mut engine)?;
+struct HelloWorld {
+    layer: LayerIndex,
+    quit: bool,
+}
 
-    'update_loop: loop {
-        // Start the frame
-        start_frame(&mut engine);
+impl App for HelloWorld {
+    type Exit = ();
 
+    fn on_event(&mut self, _engine: &mut Engine, event: &Event) {
         // 'q' to exit the program
-        for event in poll_input() {
-            if let Event::Key(KeyEvent {
-                code: KeyCode::Char('q'),
-                ..
-            }) = event
-            {
-                break 'update_loop;
-            }
+        if let Event::Key(KeyEvent {
+            code: KeyCode::Char('q'),
+            ..
+        }) = event
+        {
+            self.quit = true;
         }
+    }
 
-        // Draw contents
-        draw_text(&mut engine, layer, 14, 9, "Hello, Ferris!");
-        draw_fps_counter(&mut engine, layer, 0, 0);
+    fn draw(&mut self, engine: &mut Engine) {
+        draw_text(engine, self.layer, 14, 9, "Hello, Ferris!");
+        draw_fps_counter(engine, self.layer, 0, 0);
+    }
 
-        // End the frame
-        end_frame(&mut engine)?;
+    fn should_quit(&mut self) -> Option<()> {
+        self.quit.then_some(())
     }
+}
+
+fn main() -> io::Result<()> {
+    let mut engine = Engine::new(40, 20);
+    let layer = create_layer(&mut engine, 0);
 
-    // Restore terminal before exiting
-    exit_cleanup(&mut engine)?;
-    Ok(())
+    // Initializes the engine, runs the update loop and restores the terminal on exit
+    run(&mut engine, HelloWorld { layer, quit: false })
 }
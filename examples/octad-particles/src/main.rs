@@ -46,18 +46,20 @@ fn main() -> io::Result<()> {
                 let mut rng: ThreadRng = rand::rng();
 
                 let spec: ParticleSpec = ParticleSpec {
-                    gravity_scale: rng.random_range(0.04..0.07),
+                    gravity_scale: 0.04..=0.07,
                     speed: 5.0..=rng.random_range(30.0..120.0),
-                    lifetime_sec: 2.0,
+                    lifetime_sec: 1.6..=2.4,
                     color: ParticleColor::Gradient(ColorGradient::new(vec![
                         GradientStop::new(0.0, Color::WHITE),
                         GradientStop::new(0.13, random_bright_color(&mut rng).with_alpha(255)),
                         GradientStop::new(1.0, random_bright_color(&mut rng).with_alpha(0)),
                     ])),
+                    ..Default::default()
                 };
                 let emitter: ParticleEmitter = ParticleEmitter {
                     shape: ParticleEmitterShape::Circle,
                     count: rng.random_range(25..200),
+                    ..Default::default()
                 };
 
                 let x_a: f32 = TERM_COLS as f32 * 0.3;
@@ -7,17 +7,25 @@ use germterm::{
     engine::{Engine, end_frame, exit_cleanup, init, start_frame},
     input::poll_input,
     layer::create_layer,
-    particle::{ParticleColor, ParticleEmitter, ParticleSpec, spawn_particles},
+    particle::{
+        ParticleBudget, ParticleColor, ParticleEmitter, ParticleEvictionPolicy, ParticleSpec,
+        spawn_particles,
+    },
 };
 
 pub const TERM_COLS: u16 = 40;
 pub const TERM_ROWS: u16 = 20;
 pub const PARTICLE_COUNT: usize = 100_000;
+pub const MAX_PARTICLES: usize = 1_000_000;
 
 fn main() -> io::Result<()> {
     let mut engine: Engine = Engine::new(TERM_COLS, TERM_ROWS)
         .title("particle-benchmark")
-        .limit_fps(240);
+        .limit_fps(240)
+        .particle_budget(ParticleBudget {
+            max_particles: MAX_PARTICLES,
+            eviction: ParticleEvictionPolicy::DropOldest,
+        });
 
     let layer = create_layer(&mut engine, 0);
 
@@ -44,9 +52,10 @@ fn main() -> io::Result<()> {
                         TERM_ROWS as f32 * 0.5,
                         &ParticleSpec {
                             color: ParticleColor::Solid(Color::VIOLET),
-                            lifetime_sec: 4.0,
+                            lifetime_sec: 4.0..=4.0,
                             speed: 0.5..=35.0,
-                            gravity_scale: 0.01,
+                            gravity_scale: 0.01..=0.01,
+                            ..Default::default()
                         },
                         &ParticleEmitter {
                             count: PARTICLE_COUNT,
@@ -1,13 +1,13 @@
 use germterm::{
-    color::{Color, ColorGradient, GradientStop, sample_gradient},
-    crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind},
+    color::{Color, ColorGradient, GradientStop, sample_gradient, space::InterpolationSpace},
     draw::{draw_octad, draw_text, draw_twoxel},
     engine::{Engine, end_frame, exit_cleanup, init, start_frame},
     fps_counter::get_fps,
-    input::poll_input,
+    input::action::{ActionMap, is_action_just_pressed, load_bindings, parse_bindings},
     layer::{LayerIndex, create_layer},
     particle::{ParticleColor, ParticleEmitter, ParticleSpec, spawn_particles},
     rich_text::{Attributes, RichText},
+    timer::{TimerHandle, start_repeating_timer, timer_fired},
 };
 use rand::{Rng, rngs::ThreadRng};
 use std::io;
@@ -15,6 +15,15 @@ use std::io;
 const TERM_COLS: u16 = 40;
 const TERM_ROWS: u16 = 20;
 
+/// Used when `twoxel-snake-bindings.cfg` can't be loaded from the working directory.
+const DEFAULT_BINDINGS: &str = "\
+quit = q, esc
+move_up = w, up
+move_left = a, left
+move_down = s, down
+move_right = d, right
+";
+
 const UP: (i16, i16) = (0, -1);
 const LEFT: (i16, i16) = (-1, 0);
 const DOWN: (i16, i16) = (0, 1);
@@ -26,9 +35,15 @@ enum GameState {
 }
 
 fn main() -> io::Result<()> {
+    let actions: ActionMap = load_bindings("twoxel-snake-bindings.cfg").unwrap_or_else(|_| {
+        parse_bindings(DEFAULT_BINDINGS).expect("default bindings should be valid")
+    });
+
     let mut engine: Engine = Engine::new(TERM_COLS, TERM_ROWS)
         .title("twoxel-snake")
-        .limit_fps(0);
+        .limit_fps(0)
+        .track_input(true)
+        .actions(actions);
 
     let layer_0 = create_layer(&mut engine, 0);
     let layer_1 = create_layer(&mut engine, 1);
@@ -40,69 +55,35 @@ fn main() -> io::Result<()> {
     let mut apple_pos: (i16, i16) = random_pos();
     let mut last_direction: (i16, i16) = DOWN;
     let mut direction: (i16, i16) = DOWN;
-    let mut move_timer: f32 = 0.0;
+    let move_tick: TimerHandle = start_repeating_timer(&mut engine, 1.0 / movement_speed);
     let snake_color_gradient: ColorGradient = ColorGradient::new(vec![
         GradientStop::new(0.0, Color::CYAN),
         GradientStop::new(1.0, Color::VIOLET),
-    ]);
+    ])
+    .with_space(InterpolationSpace::Oklab);
     let mut game_state: GameState = GameState::Playing;
 
     init(&mut engine)?;
 
     'game_loop: loop {
-        for event in poll_input() {
-            match event {
-                Event::Key(KeyEvent {
-                    code: KeyCode::Char('q'),
-                    ..
-                }) => break 'game_loop,
-                Event::Key(KeyEvent {
-                    code: KeyCode::Char('w'),
-                    kind: KeyEventKind::Press,
-                    ..
-                }) => {
-                    if last_direction != DOWN {
-                        direction = UP;
-                    }
-                }
-                Event::Key(KeyEvent {
-                    code: KeyCode::Char('a'),
-                    kind: KeyEventKind::Press,
-                    ..
-                }) => {
-                    if last_direction != RIGHT {
-                        direction = LEFT
-                    }
-                }
-                Event::Key(KeyEvent {
-                    code: KeyCode::Char('s'),
-                    kind: KeyEventKind::Press,
-                    ..
-                }) => {
-                    if last_direction != UP {
-                        direction = DOWN
-                    }
-                }
-                Event::Key(KeyEvent {
-                    code: KeyCode::Char('d'),
-                    kind: KeyEventKind::Press,
-                    ..
-                }) => {
-                    if last_direction != LEFT {
-                        direction = RIGHT
-                    }
-                }
-                _ => (),
+        start_frame(&mut engine);
+
+        if is_action_just_pressed(&engine, "quit") {
+            break 'game_loop;
+        }
+        for (action, new_direction, opposite) in [
+            ("move_up", UP, DOWN),
+            ("move_left", LEFT, RIGHT),
+            ("move_down", DOWN, UP),
+            ("move_right", RIGHT, LEFT),
+        ] {
+            if is_action_just_pressed(&engine, action) && last_direction != opposite {
+                direction = new_direction;
             }
         }
 
-        start_frame(&mut engine);
-        if matches!(game_state, GameState::Playing) {
-            move_timer += engine.delta_time;
-            let step_time: f32 = 1.0 / movement_speed;
-
-            if move_timer >= step_time {
-                move_timer -= step_time;
+        for _ in 0..timer_fired(&engine, move_tick) {
+            if matches!(game_state, GameState::Playing) {
                 last_direction = direction;
 
                 let head: (i16, i16) = segments[0];
@@ -238,14 +219,15 @@ fn spawn_explosion(engine: &mut Engine, layer: LayerIndex, x: f32, y: f32) {
         x,
         y,
         &ParticleSpec {
-            gravity_scale: 0.1,
+            gravity_scale: 0.1..=0.1,
             speed: 20.0..=70.0,
-            lifetime_sec: 2.0,
+            lifetime_sec: 2.0..=2.0,
             color: ParticleColor::Gradient(ColorGradient::new(vec![
                 GradientStop::new(0.0, Color::WHITE),
                 GradientStop::new(0.05, Color::RED),
                 GradientStop::new(1.0, Color::VIOLET.with_alpha(0)),
             ])),
+            ..Default::default()
         },
         &ParticleEmitter {
             count: 30,
@@ -261,13 +243,14 @@ fn spawn_apple_create_particles(engine: &mut Engine, layer: LayerIndex, x: f32,
         x,
         y,
         &ParticleSpec {
-            gravity_scale: 0.0,
+            gravity_scale: 0.0..=0.0,
             speed: 8.0..=10.0,
-            lifetime_sec: 0.7,
+            lifetime_sec: 0.7..=0.7,
             color: ParticleColor::Gradient(ColorGradient::new(vec![
                 GradientStop::new(0.0, Color::RED.with_alpha(100)),
                 GradientStop::new(1.0, Color::RED.with_alpha(0)),
             ])),
+            ..Default::default()
         },
         &ParticleEmitter {
             count: 70,
@@ -283,14 +266,15 @@ fn spawn_death_explosion(engine: &mut Engine, layer: LayerIndex, x: f32, y: f32)
         x,
         y,
         &ParticleSpec {
-            gravity_scale: 0.5,
+            gravity_scale: 0.5..=0.5,
             speed: 10.0..=180.0,
-            lifetime_sec: 2.5,
+            lifetime_sec: 2.5..=2.5,
             color: ParticleColor::Gradient(ColorGradient::new(vec![
                 GradientStop::new(0.0, Color::WHITE),
                 GradientStop::new(0.05, Color::RED),
                 GradientStop::new(1.0, Color::YELLOW.with_alpha(0)),
             ])),
+            ..Default::default()
         },
         &ParticleEmitter {
             count: 500,
@@ -0,0 +1,74 @@
+//! Application structure for the managed run loop.
+//!
+//! Implementing [`App`] and handing it over to [`run`](crate::engine::run) replaces the hand-written
+//! `init`, `start_frame`, `end_frame` and `exit_cleanup` loop.
+
+use crate::engine::Engine;
+use crossterm::event::Event;
+
+/// An application driven by [`run`](crate::engine::run).
+///
+/// Every frame, [`App::on_event`] is called for each input event, then [`App::update`],
+/// then [`App::draw`], and finally [`App::should_quit`] decides whether the loop exits.
+///
+/// # Example
+/// ```rust,no_run
+/// # use germterm::{
+/// #     app::App,
+/// #     crossterm::event::{Event, KeyCode, KeyEvent},
+/// #     draw::draw_text,
+/// #     engine::{Engine, run},
+/// #     layer::{LayerIndex, create_layer},
+/// # };
+/// struct Hello {
+///     layer: LayerIndex,
+///     quit: bool,
+/// }
+///
+/// impl App for Hello {
+///     type Exit = ();
+///
+///     fn on_event(&mut self, _engine: &mut Engine, event: &Event) {
+///         if let Event::Key(KeyEvent { code: KeyCode::Char('q'), .. }) = event {
+///             self.quit = true;
+///         }
+///     }
+///
+///     fn draw(&mut self, engine: &mut Engine) {
+///         draw_text(engine, self.layer, 14, 9, "Hello, Ferris!");
+///     }
+///
+///     fn should_quit(&mut self) -> Option<()> {
+///         self.quit.then_some(())
+///     }
+/// }
+///
+/// let mut engine = Engine::new(40, 20);
+/// let layer = create_layer(&mut engine, 0);
+/// run(&mut engine, Hello { layer, quit: false }).unwrap();
+/// ```
+pub trait App {
+    /// The value returned by [`run`](crate::engine::run) once the app quits.
+    type Exit;
+
+    /// Advances the app state.
+    ///
+    /// Called once per frame, or once per fixed step when [`Engine::fixed_timestep`] is enabled.
+    fn update(&mut self, engine: &mut Engine) {
+        let _ = engine;
+    }
+
+    /// Draws the current app state. Called once per frame, after [`App::update`].
+    fn draw(&mut self, engine: &mut Engine);
+
+    /// Handles a single input event. Called before [`App::update`].
+    ///
+    /// Resize events are handled by the run loop using [`handle_resize`](crate::engine::handle_resize) before being passed on.
+    /// The frame keeps its size, see [`handle_resize`](crate::engine::handle_resize).
+    fn on_event(&mut self, engine: &mut Engine, event: &Event) {
+        let _ = (engine, event);
+    }
+
+    /// Called at the end of every frame. Returning `Some` exits the run loop with the given value.
+    fn should_quit(&mut self) -> Option<Self::Exit>;
+}
@@ -4,6 +4,7 @@ use crate::{color::Color, rich_text::Attributes};
 pub enum CellFormat {
     Standard,
     Twoxel,
+    HorizontalTwoxel,
     Octad,
     Blocktad,
 }
@@ -21,9 +21,26 @@
 //! ## Interpolation
 //!
 //! - [`lerp`] allows fast linear interpolation between two [`Color`]s.
+//! - [`lerp_in`] interpolates within another color space, such as Oklab.
+//!   Gradients use the space set with [`ColorGradient::with_space`].
+//!
+//! ## Color spaces
+//!
+//! The [`space`] module converts colors between HSL, HSV, linear RGB, Oklab and Oklch,
+//! and provides adjustments such as [`Color::lighten`] and [`Color::rotate_hue`].
+//!
+//! ## Serialization
+//!
+//! With the `serde` feature enabled, [`Color`] is serialized as a `"#RRGGBBAA"` hex string
+//! in human-readable formats. `"#RRGGBB"` is accepted as well, defaulting to full opacity.
+//! [`ColorGradient`] is serialized as a list of its stops.
+
+pub mod space;
 
 use std::sync::Arc;
 
+use space::{InterpolationSpace, lerp_in};
+
 pub static BLEND_ALPHA_MULT: [[u8; 256]; 256] = {
     let mut lut = [[0u8; 256]; 256];
     let mut ta = 0;
@@ -252,6 +269,7 @@ impl From<ColorRgb> for Color {
 /// and a [`Color`] at that position. Gradients are created by interpolating
 /// between multiple stops.
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GradientStop {
     pub t: f32,
     pub color: Color,
@@ -281,9 +299,13 @@ impl GradientStop {
 ///
 /// The gradient is internally reference-counted [`Arc`] so it can be
 /// cheaply cloned and shared.
+///
+/// Stops are interpolated in sRGB by default. Another [`InterpolationSpace`] can be set using
+/// [`ColorGradient::with_space`].
 #[derive(Clone)]
 pub struct ColorGradient {
     pub stops: Arc<Vec<GradientStop>>,
+    space: InterpolationSpace,
 }
 
 impl ColorGradient {
@@ -300,13 +322,116 @@ impl ColorGradient {
 
         ColorGradient {
             stops: Arc::new(stops),
+            space: InterpolationSpace::default(),
+        }
+    }
+
+    /// Sets the color space the stops are interpolated in.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use germterm::color::{Color, ColorGradient, GradientStop, space::InterpolationSpace};
+    /// let gradient = ColorGradient::new(vec![
+    ///     GradientStop::new(0.0, Color::CYAN),
+    ///     GradientStop::new(1.0, Color::VIOLET),
+    /// ])
+    /// .with_space(InterpolationSpace::Oklch);
+    /// ```
+    pub fn with_space(mut self, space: InterpolationSpace) -> Self {
+        self.space = space;
+        self
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Color {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.serialize_str(&format!("#{:08x}", self.0))
+        } else {
+            serializer.serialize_u32(self.0)
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Color {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if !deserializer.is_human_readable() {
+            return u32::deserialize(deserializer).map(Color);
         }
+
+        let value: String = String::deserialize(deserializer)?;
+        parse_hex_color(&value).ok_or_else(|| {
+            serde::de::Error::custom(format!(
+                "invalid color `{value}`, expected `#RRGGBB` or `#RRGGBBAA`"
+            ))
+        })
+    }
+}
+
+/// Parses a `#RRGGBB` or `#RRGGBBAA` hex string.
+#[cfg(feature = "serde")]
+fn parse_hex_color(value: &str) -> Option<Color> {
+    let hex: &str = value.strip_prefix('#')?;
+    if !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    let packed: u32 = u32::from_str_radix(hex, 16).ok()?;
+    match hex.len() {
+        6 => Some(Color((packed << 8) | 0xFF)),
+        8 => Some(Color(packed)),
+        _ => None,
+    }
+}
+
+/// A gradient is serialized as just its stops when interpolated in sRGB.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(untagged)]
+enum ColorGradientRepr {
+    Stops(Vec<GradientStop>),
+    WithSpace {
+        stops: Vec<GradientStop>,
+        #[serde(default)]
+        space: InterpolationSpace,
+    },
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for ColorGradient {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let stops: Vec<GradientStop> = self.stops.to_vec();
+        match self.space {
+            InterpolationSpace::Srgb => ColorGradientRepr::Stops(stops),
+            space => ColorGradientRepr::WithSpace { stops, space },
+        }
+        .serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for ColorGradient {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let (stops, space) = match ColorGradientRepr::deserialize(deserializer)? {
+            ColorGradientRepr::Stops(stops) => (stops, InterpolationSpace::default()),
+            ColorGradientRepr::WithSpace { stops, space } => (stops, space),
+        };
+        if stops.is_empty() {
+            return Err(serde::de::Error::custom(
+                "gradient must have at least 1 stop",
+            ));
+        }
+        Ok(ColorGradient::new(stops).with_space(space))
     }
 }
 
 /// Samples a color from a `ColorGradient` at a normalized position `t`.
 ///
 /// `t` should be in the range `0.0..=1.0`. Values outside this range are clamped.
+/// Stops are interpolated in the gradient's [`InterpolationSpace`].
 ///
 /// # Example
 ///
@@ -332,7 +457,7 @@ pub fn sample_gradient(gradient: &ColorGradient, t: f32) -> Color {
 
         if t >= a.t && t <= b.t {
             let local_t = (t - a.t) / (b.t - a.t);
-            return lerp(a.color, b.color, local_t);
+            return lerp_in(a.color, b.color, local_t, gradient.space);
         }
     }
 
@@ -371,6 +496,15 @@ pub fn lerp(a: Color, b: Color, t: f32) -> Color {
     Color::new(out_r, out_g, out_b, out_a)
 }
 
+/// Approximates the perceived brightness of a [`Color`] using the Rec. 709 luma coefficients.
+///
+/// The alpha channel is ignored.
+#[inline]
+pub(crate) fn luminance(color: Color) -> u16 {
+    let (r, g, b) = color.rgb();
+    ((r as u32 * 54 + g as u32 * 183 + b as u32 * 19) >> 8) as u16
+}
+
 #[inline]
 pub(crate) fn blend_source_over(bottom: Color, top: Color) -> Color {
     let (tr, tg, tb, ta) = top.rgba();
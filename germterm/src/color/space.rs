@@ -0,0 +1,503 @@
+//! Color spaces and color adjustments.
+//!
+//! [`Color`] converts from and into the following color spaces using [`From`]:
+//!
+//! - [`Hsl`] and [`Hsv`], describing colors by hue, saturation and lightness or value.
+//! - [`LinearRgb`], RGB without the sRGB gamma curve, where light mixes physically.
+//! - [`Oklab`] and its polar form [`Oklch`], perceptually uniform spaces where equal steps look equally large.
+//!
+//! Colors can be adjusted using [`Color::lighten`], [`Color::darken`], [`Color::saturate`],
+//! [`Color::desaturate`] and [`Color::rotate_hue`], and interpolated in any of the spaces using [`lerp_in`].
+//!
+//! Channels are `f32`s, with hues in degrees within `0.0..360.0` and all other channels,
+//! including alpha, usually within `0.0..=1.0`. Colors outside the sRGB gamut are clamped when converted back.
+//!
+//! # Example
+//! ```rust
+//! # use germterm::color::{Color, space::Hsl};
+//! let hsl = Hsl::from(Color::RED);
+//! assert_eq!((hsl.h, hsl.s, hsl.l), (0.0, 1.0, 0.5));
+//! assert_eq!(Color::RED.rotate_hue(120.0), Color::GREEN);
+//! ```
+
+use std::f32::consts::PI;
+
+use crate::color::{Color, lerp};
+
+/// A color described by hue, saturation and lightness.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Hsl {
+    pub h: f32,
+    pub s: f32,
+    pub l: f32,
+    pub alpha: f32,
+}
+
+/// A color described by hue, saturation and value.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Hsv {
+    pub h: f32,
+    pub s: f32,
+    pub v: f32,
+    pub alpha: f32,
+}
+
+/// A color in linear RGB, without the sRGB gamma curve.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LinearRgb {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+    pub alpha: f32,
+}
+
+/// A color in the perceptually uniform Oklab space.
+///
+/// `l` is the perceived lightness, `a` goes from green to red and `b` from blue to yellow.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Oklab {
+    pub l: f32,
+    pub a: f32,
+    pub b: f32,
+    pub alpha: f32,
+}
+
+/// A color in Oklch, the polar form of [`Oklab`], described by lightness, chroma and hue.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Oklch {
+    pub l: f32,
+    pub c: f32,
+    pub h: f32,
+    pub alpha: f32,
+}
+
+/// The color space colors are interpolated in, see [`lerp_in`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum InterpolationSpace {
+    /// Interpolates the sRGB channels using the fast [`lerp`].
+    /// Mixing saturated colors of different hues goes dark and muddy in the middle.
+    #[default]
+    Srgb,
+    /// Physically mixes light. Brighter in the middle than [`InterpolationSpace::Srgb`].
+    LinearRgb,
+    /// Perceptually even steps without muddy middles. Usually the best choice for gradients.
+    Oklab,
+    /// Like [`InterpolationSpace::Oklab`], but goes around the hue circle along the shorter way,
+    /// keeping colors vivid in the middle.
+    Oklch,
+    /// Goes around the hue circle along the shorter way, keeping saturation.
+    Hsl,
+}
+
+/// Interpolates between two [`Color`]s within a color space.
+///
+/// `t = 0.0` returns `a` and `t = 1.0` returns `b`. Values outside `0.0..=1.0` are clamped to this range.
+///
+/// # Example
+/// ```rust
+/// # use germterm::color::{Color, space::{InterpolationSpace, lerp_in}};
+/// let middle = lerp_in(Color::CYAN, Color::VIOLET, 0.5, InterpolationSpace::Oklab);
+/// assert_eq!(lerp_in(Color::CYAN, Color::VIOLET, 1.0, InterpolationSpace::Oklch), Color::VIOLET);
+/// ```
+pub fn lerp_in(a: Color, b: Color, t: f32, space: InterpolationSpace) -> Color {
+    let t: f32 = t.clamp(0.0, 1.0);
+    match space {
+        InterpolationSpace::Srgb => lerp(a, b, t),
+        InterpolationSpace::LinearRgb => {
+            let (a, b) = (LinearRgb::from(a), LinearRgb::from(b));
+            LinearRgb {
+                r: mix(a.r, b.r, t),
+                g: mix(a.g, b.g, t),
+                b: mix(a.b, b.b, t),
+                alpha: mix(a.alpha, b.alpha, t),
+            }
+            .into()
+        }
+        InterpolationSpace::Oklab => {
+            let (a, b) = (Oklab::from(a), Oklab::from(b));
+            Oklab {
+                l: mix(a.l, b.l, t),
+                a: mix(a.a, b.a, t),
+                b: mix(a.b, b.b, t),
+                alpha: mix(a.alpha, b.alpha, t),
+            }
+            .into()
+        }
+        InterpolationSpace::Oklch => {
+            let (a, b) = (Oklch::from(a), Oklch::from(b));
+            // Greys have no hue, so they take on the hue of the other color
+            let (a_h, b_h) = hues_for_mixing(a.h, a.c, b.h, b.c, OKLCH_ACHROMATIC);
+            Oklch {
+                l: mix(a.l, b.l, t),
+                c: mix(a.c, b.c, t),
+                h: mix_hue(a_h, b_h, t),
+                alpha: mix(a.alpha, b.alpha, t),
+            }
+            .into()
+        }
+        InterpolationSpace::Hsl => {
+            let (a, b) = (Hsl::from(a), Hsl::from(b));
+            let (a_h, b_h) = hues_for_mixing(a.h, a.s, b.h, b.s, 0.0);
+            Hsl {
+                h: mix_hue(a_h, b_h, t),
+                s: mix(a.s, b.s, t),
+                l: mix(a.l, b.l, t),
+                alpha: mix(a.alpha, b.alpha, t),
+            }
+            .into()
+        }
+    }
+}
+
+// Chroma below which a color counts as grey in Oklch, hiding rounding noise of 8-bit greys
+const OKLCH_ACHROMATIC: f32 = 0.0001;
+
+fn hues_for_mixing(a_h: f32, a_c: f32, b_h: f32, b_c: f32, achromatic: f32) -> (f32, f32) {
+    match (a_c <= achromatic, b_c <= achromatic) {
+        (true, false) => (b_h, b_h),
+        (false, true) => (a_h, a_h),
+        _ => (a_h, b_h),
+    }
+}
+
+#[inline]
+fn mix(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+/// Interpolates hues in degrees along the shorter way around the circle.
+fn mix_hue(a: f32, b: f32, t: f32) -> f32 {
+    let difference: f32 = (b - a + 540.0).rem_euclid(360.0) - 180.0;
+    (a + difference * t).rem_euclid(360.0)
+}
+
+impl Color {
+    /// Increases the HSL lightness by `amount`, eg. `0.1` for 10 percentage points.
+    pub fn lighten(&self, amount: f32) -> Color {
+        let hsl: Hsl = Hsl::from(*self);
+        Hsl {
+            l: (hsl.l + amount).clamp(0.0, 1.0),
+            ..hsl
+        }
+        .into()
+    }
+
+    /// Decreases the HSL lightness by `amount`, eg. `0.1` for 10 percentage points.
+    pub fn darken(&self, amount: f32) -> Color {
+        self.lighten(-amount)
+    }
+
+    /// Increases the HSL saturation by `amount`, eg. `0.1` for 10 percentage points.
+    pub fn saturate(&self, amount: f32) -> Color {
+        let hsl: Hsl = Hsl::from(*self);
+        Hsl {
+            s: (hsl.s + amount).clamp(0.0, 1.0),
+            ..hsl
+        }
+        .into()
+    }
+
+    /// Decreases the HSL saturation by `amount`, eg. `0.1` for 10 percentage points.
+    pub fn desaturate(&self, amount: f32) -> Color {
+        self.saturate(-amount)
+    }
+
+    /// Rotates the HSL hue by `degrees`.
+    pub fn rotate_hue(&self, degrees: f32) -> Color {
+        let hsl: Hsl = Hsl::from(*self);
+        Hsl {
+            h: (hsl.h + degrees).rem_euclid(360.0),
+            ..hsl
+        }
+        .into()
+    }
+}
+
+/// Packs `0.0..=1.0` channels into a [`Color`], rounding to the nearest value.
+fn pack(r: f32, g: f32, b: f32, alpha: f32) -> Color {
+    let channel = |value: f32| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+    Color::new(channel(r), channel(g), channel(b), channel(alpha))
+}
+
+/// Hue in degrees, along with the largest and smallest channel.
+fn hue(r: f32, g: f32, b: f32) -> (f32, f32, f32) {
+    let max: f32 = r.max(g).max(b);
+    let min: f32 = r.min(g).min(b);
+    let range: f32 = max - min;
+
+    let h: f32 = if range <= 0.0 {
+        0.0
+    } else if max == r {
+        60.0 * ((g - b) / range).rem_euclid(6.0)
+    } else if max == g {
+        60.0 * ((b - r) / range + 2.0)
+    } else {
+        60.0 * ((r - g) / range + 4.0)
+    };
+    (h, max, min)
+}
+
+/// RGB channels of a fully saturated color with the hue `h`, scaled by `chroma` and offset by `min`.
+fn from_hue(h: f32, chroma: f32, min: f32) -> (f32, f32, f32) {
+    let h: f32 = h.rem_euclid(360.0) / 60.0;
+    let x: f32 = chroma * (1.0 - (h.rem_euclid(2.0) - 1.0).abs());
+    let (r, g, b) = match h as u32 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+    (r + min, g + min, b + min)
+}
+
+impl From<Color> for Hsl {
+    fn from(color: Color) -> Self {
+        let (r, g, b, alpha) = color.rgba_f32();
+        let (h, max, min) = hue(r, g, b);
+        let l: f32 = (max + min) / 2.0;
+        let s: f32 = if max == min {
+            0.0
+        } else {
+            (max - min) / (1.0 - (2.0 * l - 1.0).abs())
+        };
+        Hsl { h, s, l, alpha }
+    }
+}
+
+impl From<Hsl> for Color {
+    fn from(hsl: Hsl) -> Self {
+        let (s, l) = (hsl.s.clamp(0.0, 1.0), hsl.l.clamp(0.0, 1.0));
+        let chroma: f32 = (1.0 - (2.0 * l - 1.0).abs()) * s;
+        let (r, g, b) = from_hue(hsl.h, chroma, l - chroma / 2.0);
+        pack(r, g, b, hsl.alpha)
+    }
+}
+
+impl From<Color> for Hsv {
+    fn from(color: Color) -> Self {
+        let (r, g, b, alpha) = color.rgba_f32();
+        let (h, max, min) = hue(r, g, b);
+        let s: f32 = if max <= 0.0 { 0.0 } else { (max - min) / max };
+        Hsv {
+            h,
+            s,
+            v: max,
+            alpha,
+        }
+    }
+}
+
+impl From<Hsv> for Color {
+    fn from(hsv: Hsv) -> Self {
+        let (s, v) = (hsv.s.clamp(0.0, 1.0), hsv.v.clamp(0.0, 1.0));
+        let chroma: f32 = v * s;
+        let (r, g, b) = from_hue(hsv.h, chroma, v - chroma);
+        pack(r, g, b, hsv.alpha)
+    }
+}
+
+fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(value: f32) -> f32 {
+    if value <= 0.0031308 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+impl From<Color> for LinearRgb {
+    fn from(color: Color) -> Self {
+        let (r, g, b, alpha) = color.rgba_f32();
+        LinearRgb {
+            r: srgb_to_linear(r),
+            g: srgb_to_linear(g),
+            b: srgb_to_linear(b),
+            alpha,
+        }
+    }
+}
+
+impl From<LinearRgb> for Color {
+    fn from(linear: LinearRgb) -> Self {
+        pack(
+            linear_to_srgb(linear.r.clamp(0.0, 1.0)),
+            linear_to_srgb(linear.g.clamp(0.0, 1.0)),
+            linear_to_srgb(linear.b.clamp(0.0, 1.0)),
+            linear.alpha,
+        )
+    }
+}
+
+impl From<LinearRgb> for Oklab {
+    fn from(linear: LinearRgb) -> Self {
+        let LinearRgb { r, g, b, alpha } = linear;
+        let l: f32 = (0.41222146 * r + 0.53633254 * g + 0.051445995 * b).cbrt();
+        let m: f32 = (0.2119035 * r + 0.6806995 * g + 0.10739696 * b).cbrt();
+        let s: f32 = (0.08830246 * r + 0.28171885 * g + 0.6299787 * b).cbrt();
+        Oklab {
+            l: 0.21045426 * l + 0.7936178 * m - 0.004072047 * s,
+            a: 1.9779985 * l - 2.4285922 * m + 0.4505937 * s,
+            b: 0.025904037 * l + 0.78277177 * m - 0.80867577 * s,
+            alpha,
+        }
+    }
+}
+
+impl From<Oklab> for LinearRgb {
+    fn from(oklab: Oklab) -> Self {
+        let Oklab { l, a, b, alpha } = oklab;
+        let l_: f32 = (l + 0.39633778 * a + 0.21580376 * b).powi(3);
+        let m_: f32 = (l - 0.105561346 * a - 0.06385417 * b).powi(3);
+        let s_: f32 = (l - 0.08948418 * a - 1.2914855 * b).powi(3);
+        LinearRgb {
+            r: 4.0767417 * l_ - 3.3077116 * m_ + 0.23096994 * s_,
+            g: -1.268438 * l_ + 2.6097574 * m_ - 0.34131938 * s_,
+            b: -0.0041960864 * l_ - 0.7034186 * m_ + 1.7076147 * s_,
+            alpha,
+        }
+    }
+}
+
+impl From<Color> for Oklab {
+    fn from(color: Color) -> Self {
+        LinearRgb::from(color).into()
+    }
+}
+
+impl From<Oklab> for Color {
+    fn from(oklab: Oklab) -> Self {
+        LinearRgb::from(oklab).into()
+    }
+}
+
+impl From<Oklab> for Oklch {
+    fn from(oklab: Oklab) -> Self {
+        Oklch {
+            l: oklab.l,
+            c: oklab.a.hypot(oklab.b),
+            h: oklab.b.atan2(oklab.a).to_degrees().rem_euclid(360.0),
+            alpha: oklab.alpha,
+        }
+    }
+}
+
+impl From<Oklch> for Oklab {
+    fn from(oklch: Oklch) -> Self {
+        let h: f32 = oklch.h * PI / 180.0;
+        Oklab {
+            l: oklch.l,
+            a: oklch.c * h.cos(),
+            b: oklch.c * h.sin(),
+            alpha: oklch.alpha,
+        }
+    }
+}
+
+impl From<Color> for Oklch {
+    fn from(color: Color) -> Self {
+        Oklab::from(color).into()
+    }
+}
+
+impl From<Oklch> for Color {
+    fn from(oklch: Oklch) -> Self {
+        Oklab::from(oklch).into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Colors spread over the whole 8-bit RGB cube, with varying alpha.
+    fn samples() -> impl Iterator<Item = Color> {
+        (0..=255u8).step_by(15).flat_map(|r| {
+            (0..=255u8).step_by(15).flat_map(move |g| {
+                (0..=255u8)
+                    .step_by(15)
+                    .map(move |b| Color::new(r, g, b, r ^ g ^ b))
+            })
+        })
+    }
+
+    #[test]
+    fn colors_round_trip_through_every_space() {
+        for color in samples() {
+            assert_eq!(Color::from(Hsl::from(color)), color, "Hsl");
+            assert_eq!(Color::from(Hsv::from(color)), color, "Hsv");
+            assert_eq!(Color::from(LinearRgb::from(color)), color, "LinearRgb");
+            assert_eq!(Color::from(Oklab::from(color)), color, "Oklab");
+            assert_eq!(Color::from(Oklch::from(color)), color, "Oklch");
+        }
+    }
+
+    #[test]
+    fn lerp_in_returns_the_endpoints() {
+        let (a, b) = (Color::new(20, 200, 90, 255), Color::new(240, 30, 160, 128));
+        for space in [
+            InterpolationSpace::Srgb,
+            InterpolationSpace::LinearRgb,
+            InterpolationSpace::Oklab,
+            InterpolationSpace::Oklch,
+            InterpolationSpace::Hsl,
+        ] {
+            assert_eq!(lerp_in(a, b, 0.0, space), a, "{space:?}");
+            assert_eq!(lerp_in(a, b, 1.0, space), b, "{space:?}");
+            assert_eq!(lerp_in(a, b, -1.0, space), a, "{space:?}");
+            assert_eq!(lerp_in(a, b, 2.0, space), b, "{space:?}");
+        }
+    }
+
+    #[test]
+    fn hues_mix_the_short_way_around() {
+        assert!((mix_hue(350.0, 10.0, 0.5) - 0.0).abs() < 1e-4);
+        assert!((mix_hue(10.0, 350.0, 0.25) - 5.0).abs() < 1e-4);
+        assert!((mix_hue(90.0, 180.0, 0.5) - 135.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn greys_take_on_the_hue_of_the_other_color() {
+        let middle: Color = lerp_in(
+            Color::new(128, 128, 128, 255),
+            Color::RED,
+            0.5,
+            InterpolationSpace::Hsl,
+        );
+        let hsl: Hsl = Hsl::from(middle);
+        assert!(hsl.h < 1.0 || hsl.h > 359.0);
+        assert!(hsl.s > 0.0);
+    }
+
+    #[test]
+    fn rotating_the_hue_cycles_the_primaries() {
+        assert_eq!(Color::RED.rotate_hue(120.0), Color::GREEN);
+        assert_eq!(Color::GREEN.rotate_hue(120.0), Color::BLUE);
+        assert_eq!(Color::BLUE.rotate_hue(-120.0), Color::GREEN);
+        for color in samples() {
+            assert_eq!(color.rotate_hue(360.0), color);
+        }
+    }
+
+    #[test]
+    fn adjustments_clamp_lightness_and_saturation() {
+        assert_eq!(Color::RED.lighten(1.0), Color::new(255, 255, 255, 255));
+        assert_eq!(Color::RED.darken(1.0), Color::new(0, 0, 0, 255));
+        assert_eq!(Color::RED.desaturate(1.0), Color::new(128, 128, 128, 255));
+        assert_eq!(Color::RED.saturate(1.0), Color::RED);
+    }
+}
@@ -16,7 +16,7 @@
 //!
 //! All drawing functions operate in the same coordinate space, where `x` and `y` refer to terminal columns and rows.
 //!
-//! Some primitives (such as [`draw_octad`] and [`draw_twoxel`]) allow for a higher
+//! Some primitives (such as [`draw_octad`], [`draw_twoxel`] and [`draw_horizontal_twoxel`]) allow for a higher
 //! sub-cell drawing precision using floating point coordinates combined with Unicode tricks.
 //!
 //! Most terminal emulators use an aspect ratio close to `1:2` (`width`:`height`).
@@ -170,6 +170,7 @@ pub fn draw_rect(
 /// When drawing multiple octads to the same cell, at differing sub-positions, the octads will merge into a single multi-dot character.
 /// Merged octads possess a technical limitation of having to share the same `fg` color.
 /// Because of this, the entire merged octad cluster inherits the `fg` color of the last drawn octad in the cell.
+/// This behavior can be changed with [`Engine::merge_policy`], eg. to average the colors of all contributing octads instead.
 ///
 /// # Example
 /// ```rust,no_run
@@ -221,6 +222,7 @@ pub fn draw_octad(engine: &mut Engine, layer_index: LayerIndex, x: f32, y: f32,
 /// When drawing multiple blocktads to the same cell, at differing sub-positions, the blocktads will merge into a single character representing both.
 /// Merged blocktads possess a technical limitation of having to share the same `fg` color.
 /// Because of this, the entire merged blocktad cluster inherits the `fg` color of the last drawn blocktad in the cell.
+/// This behavior can be changed with [`Engine::merge_policy`], eg. to average the colors of all contributing blocktads instead.
 ///
 /// # Example
 /// ```rust,no_run
@@ -297,6 +299,50 @@ pub fn draw_twoxel(engine: &mut Engine, layer_index: LayerIndex, x: f32, y: f32,
     draw_text(engine, layer_index, cell_x, cell_y, rich_text)
 }
 
+/// Draws a single horizontal twoxel at the specified sub-cell position.
+///
+/// A single horizontal twoxel is represented by one of the half block characters (`▌` or `▐`) from the [Block Elements unicode block](https://en.wikipedia.org/wiki/Block_Elements).
+///
+/// The character will be drawn in one of the 2 possible horizontal sub-positions of a cell,
+/// based on the passed floating point coordinates.
+///
+/// The coordinate space is based on cols and rows (`x` and `y`), just like the rest of the drawing API.
+///
+/// Horizontal twoxels follow the same merging rules as [`draw_twoxel`].
+/// When drawing a horizontal twoxel on top of an opposing horizontal twoxel occupying the same cell, both twoxels will be merged into the same cell,
+/// displaying their colors fully independently on one another.
+/// Horizontal twoxels do not merge with vertical ones.
+///
+/// # Example
+/// ```rust,no_run
+/// # use germterm::{draw::draw_horizontal_twoxel, layer::create_layer, engine::Engine, color::Color};
+/// let mut engine = Engine::new(40, 20);
+/// let layer = create_layer(&mut engine, 0);
+///
+/// // The following horizontal twoxels would occupy the same cell,
+/// // resulting in a merged twoxel with independent colors
+/// draw_horizontal_twoxel(&mut engine, layer, 3.0, 4.0, Color::RED);
+/// draw_horizontal_twoxel(&mut engine, layer, 3.5, 4.0, Color::CYAN);
+/// ```
+pub fn draw_horizontal_twoxel(
+    engine: &mut Engine,
+    layer_index: LayerIndex,
+    x: f32,
+    y: f32,
+    color: Color,
+) {
+    let cell_x: i16 = x.floor() as i16;
+    let cell_y: i16 = y.floor() as i16;
+
+    let is_right_half: bool = x - cell_x as f32 >= 0.5;
+    let half_block: char = if is_right_half { '▐' } else { '▌' };
+    let rich_text: RichText = RichText::new(half_block.to_string())
+        .with_fg(color)
+        .with_cell_format(CellFormat::HorizontalTwoxel);
+
+    draw_text(engine, layer_index, cell_x, cell_y, rich_text)
+}
+
 /// Draws the current FPS.
 ///
 /// The retrieved value is an EMA (Exponential Moving Average).
@@ -0,0 +1,276 @@
+//! Easing functions and curves.
+//!
+//! ## Easing
+//!
+//! - [`Easing`] selects one of the built-in easing functions.
+//! - [`ease`] maps a normalized `0.0..=1.0` progress value through an easing function.
+//!
+//! ## Curves
+//!
+//! - [`Curve`] describes how a value changes along a normalized `0.0..=1.0` range,
+//!   either through an easing function or keyframes.
+//! - [`sample_curve`] evaluates a curve at a given position.
+
+use std::{f32::consts::PI, sync::Arc};
+
+/// Built-in easing functions.
+///
+/// `In` variants start slow, `Out` variants end slow and `InOut` variants do both.
+///
+/// `Elastic` and `Back` variants overshoot, returning values outside `0.0..=1.0` in between.
+/// `Bounce` variants bounce off their end (or start) like a dropped ball.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Easing {
+    #[default]
+    Linear,
+    QuadIn,
+    QuadOut,
+    QuadInOut,
+    CubicIn,
+    CubicOut,
+    CubicInOut,
+    SineIn,
+    SineOut,
+    SineInOut,
+    ElasticIn,
+    ElasticOut,
+    ElasticInOut,
+    BounceIn,
+    BounceOut,
+    BounceInOut,
+    BackIn,
+    BackOut,
+    BackInOut,
+}
+
+const BACK_OVERSHOOT: f32 = 1.70158;
+const BACK_IN_OUT_OVERSHOOT: f32 = BACK_OVERSHOOT * 1.525;
+const ELASTIC_PERIOD: f32 = 2.0 * PI / 3.0;
+const ELASTIC_IN_OUT_PERIOD: f32 = 2.0 * PI / 4.5;
+
+/// Maps a normalized progress value `t` through an easing function.
+///
+/// `t` should be in the range `0.0..=1.0`. Values outside this range are clamped.
+///
+/// # Example
+/// ```rust
+/// # use germterm::easing::{Easing, ease};
+/// assert_eq!(ease(Easing::QuadIn, 0.5), 0.25);
+/// ```
+pub fn ease(easing: Easing, t: f32) -> f32 {
+    let t: f32 = t.clamp(0.0, 1.0);
+
+    match easing {
+        Easing::Linear => t,
+        Easing::QuadIn => t * t,
+        Easing::QuadOut => 1.0 - (1.0 - t) * (1.0 - t),
+        Easing::QuadInOut => {
+            if t < 0.5 {
+                2.0 * t * t
+            } else {
+                1.0 - (-2.0 * t + 2.0).powi(2) / 2.0
+            }
+        }
+        Easing::CubicIn => t * t * t,
+        Easing::CubicOut => 1.0 - (1.0 - t).powi(3),
+        Easing::CubicInOut => {
+            if t < 0.5 {
+                4.0 * t * t * t
+            } else {
+                1.0 - (-2.0 * t + 2.0).powi(3) / 2.0
+            }
+        }
+        Easing::SineIn => 1.0 - (t * PI / 2.0).cos(),
+        Easing::SineOut => (t * PI / 2.0).sin(),
+        Easing::SineInOut => -((t * PI).cos() - 1.0) / 2.0,
+        Easing::ElasticIn => match t {
+            0.0 | 1.0 => t,
+            _ => -(2.0f32).powf(10.0 * t - 10.0) * ((t * 10.0 - 10.75) * ELASTIC_PERIOD).sin(),
+        },
+        Easing::ElasticOut => match t {
+            0.0 | 1.0 => t,
+            _ => (2.0f32).powf(-10.0 * t) * ((t * 10.0 - 0.75) * ELASTIC_PERIOD).sin() + 1.0,
+        },
+        Easing::ElasticInOut => match t {
+            0.0 | 1.0 => t,
+            _ if t < 0.5 => {
+                -((2.0f32).powf(20.0 * t - 10.0)
+                    * ((20.0 * t - 11.125) * ELASTIC_IN_OUT_PERIOD).sin())
+                    / 2.0
+            }
+            _ => {
+                (2.0f32).powf(-20.0 * t + 10.0)
+                    * ((20.0 * t - 11.125) * ELASTIC_IN_OUT_PERIOD).sin()
+                    / 2.0
+                    + 1.0
+            }
+        },
+        Easing::BounceIn => 1.0 - bounce_out(1.0 - t),
+        Easing::BounceOut => bounce_out(t),
+        Easing::BounceInOut => {
+            if t < 0.5 {
+                (1.0 - bounce_out(1.0 - 2.0 * t)) / 2.0
+            } else {
+                (1.0 + bounce_out(2.0 * t - 1.0)) / 2.0
+            }
+        }
+        Easing::BackIn => (BACK_OVERSHOOT + 1.0) * t * t * t - BACK_OVERSHOOT * t * t,
+        Easing::BackOut => {
+            1.0 + (BACK_OVERSHOOT + 1.0) * (t - 1.0).powi(3) + BACK_OVERSHOOT * (t - 1.0).powi(2)
+        }
+        Easing::BackInOut => {
+            if t < 0.5 {
+                (2.0 * t).powi(2)
+                    * ((BACK_IN_OUT_OVERSHOOT + 1.0) * 2.0 * t - BACK_IN_OUT_OVERSHOOT)
+                    / 2.0
+            } else {
+                ((2.0 * t - 2.0).powi(2)
+                    * ((BACK_IN_OUT_OVERSHOOT + 1.0) * (2.0 * t - 2.0) + BACK_IN_OUT_OVERSHOOT)
+                    + 2.0)
+                    / 2.0
+            }
+        }
+    }
+}
+
+fn bounce_out(t: f32) -> f32 {
+    const N: f32 = 7.5625;
+    const D: f32 = 2.75;
+
+    if t < 1.0 / D {
+        N * t * t
+    } else if t < 2.0 / D {
+        let t: f32 = t - 1.5 / D;
+        N * t * t + 0.75
+    } else if t < 2.5 / D {
+        let t: f32 = t - 2.25 / D;
+        N * t * t + 0.9375
+    } else {
+        let t: f32 = t - 2.625 / D;
+        N * t * t + 0.984375
+    }
+}
+
+/// Describes how a value changes along a normalized `0.0..=1.0` range.
+///
+/// Sample a curve using [`sample_curve`].
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Curve {
+    /// The same value along the entire range.
+    Constant(f32),
+    /// Goes from `from` to `to` using an easing function.
+    Eased { from: f32, to: f32, easing: Easing },
+    /// Linearly interpolates between `(t, value)` keyframes.
+    ///
+    /// Keyframes should be sorted by `t`. Values before the first and after the last keyframe are held.
+    Keyframes(Arc<Vec<(f32, f32)>>),
+}
+
+/// Samples a [`Curve`] at a normalized position `t`.
+///
+/// `t` should be in the range `0.0..=1.0`. Values outside this range are clamped.
+///
+/// # Example
+/// ```rust
+/// # use germterm::easing::{Curve, Easing, sample_curve};
+/// let fade_out = Curve::Eased {
+///     from: 1.0,
+///     to: 0.0,
+///     easing: Easing::Linear,
+/// };
+/// assert_eq!(sample_curve(&fade_out, 0.25), 0.75);
+/// ```
+pub fn sample_curve(curve: &Curve, t: f32) -> f32 {
+    let t: f32 = t.clamp(0.0, 1.0);
+
+    match curve {
+        Curve::Constant(value) => *value,
+        Curve::Eased { from, to, easing } => from + (to - from) * ease(*easing, t),
+        Curve::Keyframes(keyframes) => {
+            let Some(first) = keyframes.first() else {
+                return 0.0;
+            };
+            if t <= first.0 {
+                return first.1;
+            }
+
+            for window in keyframes.windows(2) {
+                let (a_t, a_value) = window[0];
+                let (b_t, b_value) = window[1];
+
+                if t >= a_t && t <= b_t {
+                    if b_t - a_t <= f32::EPSILON {
+                        return b_value;
+                    }
+                    let local_t: f32 = (t - a_t) / (b_t - a_t);
+                    return a_value + (b_value - a_value) * local_t;
+                }
+            }
+
+            keyframes.last().unwrap().1
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EASINGS: [Easing; 19] = [
+        Easing::Linear,
+        Easing::QuadIn,
+        Easing::QuadOut,
+        Easing::QuadInOut,
+        Easing::CubicIn,
+        Easing::CubicOut,
+        Easing::CubicInOut,
+        Easing::SineIn,
+        Easing::SineOut,
+        Easing::SineInOut,
+        Easing::ElasticIn,
+        Easing::ElasticOut,
+        Easing::ElasticInOut,
+        Easing::BounceIn,
+        Easing::BounceOut,
+        Easing::BounceInOut,
+        Easing::BackIn,
+        Easing::BackOut,
+        Easing::BackInOut,
+    ];
+
+    #[test]
+    fn easings_start_at_zero_and_end_at_one() {
+        for easing in EASINGS {
+            assert!(
+                ease(easing, 0.0).abs() < 1e-5,
+                "{easing:?} doesn't start at 0"
+            );
+            assert!(
+                (ease(easing, 1.0) - 1.0).abs() < 1e-5,
+                "{easing:?} doesn't end at 1"
+            );
+        }
+    }
+
+    #[test]
+    fn in_out_easings_are_symmetric() {
+        for easing in [
+            Easing::QuadInOut,
+            Easing::CubicInOut,
+            Easing::SineInOut,
+            Easing::ElasticInOut,
+            Easing::BounceInOut,
+            Easing::BackInOut,
+        ] {
+            for t in [0.1, 0.25, 0.4] {
+                let sum: f32 = ease(easing, t) + ease(easing, 1.0 - t);
+                assert!(
+                    (sum - 1.0).abs() < 1e-4,
+                    "{easing:?} isn't symmetric at {t}"
+                );
+            }
+        }
+    }
+}
@@ -5,30 +5,97 @@
 //! Essentially, this is the central "body" that coordinates everything.
 
 use crate::{
+    app::App,
     color::{Color, ColorRgb},
     draw::erase_rect,
+    fixed_timestep::{FixedTimestep, advance_fixed_timestep, fixed_steps, interpolation_alpha},
     fps_counter::{FpsCounter, update_fps_counter},
     fps_limiter::{self, FpsLimiter, wait_for_next_frame},
-    frame::{FramePair, compose_frame_buffer, draw_to_terminal},
+    frame::{FramePair, MergePolicy, compose_frame_buffer, draw_to_terminal},
+    input::{
+        InputState,
+        action::{ActionMap, ActionState, set_action_map, update_action_state},
+        gesture::{GestureConfig, GestureState, update_gestures},
+        hit_region::{HitRegions, swap_hit_regions},
+        poll_input, query_cell_pixel_size,
+        recording::{InputSource, RecordedFrame, next_replay_frame, record_frame},
+        update_input_state,
+    },
     layer::{Layer, LayerIndex, create_layer},
-    particle::{ParticleState, update_and_draw_particles},
+    particle::{
+        EmitterState, ForceField, ForceFieldHandle, ParticleBudget, ParticleEvent, ParticleState,
+        PendingSubEmitter, SolidHandle, SolidMask, SolidRect, default_particle_rng, draw_particles,
+        seed_particle_rng, set_particle_budget, update_particles,
+    },
+    timer::{Timer, update_timers},
+    tween::{PlayingTween, TweenValue, update_tweens},
 };
-use crossterm::{cursor, event, execute, terminal};
+use crossterm::{
+    cursor,
+    event::{self, Event},
+    execute, style, terminal,
+};
+use rand::RngCore;
 use std::{
-    io::{self},
+    io::{self, Write},
+    panic::{self, AssertUnwindSafe, PanicHookInfo},
+    sync::Arc,
+    thread,
     time::Duration,
 };
 
 pub struct Engine {
+    /// Game time elapsed since the previous frame in seconds.
+    ///
+    /// This is [`Engine::real_delta_time`] multiplied by the [time scale](set_time_scale), or `0.0` while [paused](set_paused).
     pub delta_time: f32,
+    /// Total simulated time in seconds.
+    ///
+    /// Advances by [`Engine::delta_time`] every frame, or by the fixed steps taken
+    /// when [`Engine::fixed_timestep`] is enabled.
     pub game_time: f32,
+    /// Real time elapsed since the previous frame in seconds, unaffected by the time scale and pausing.
+    ///
+    /// Useful for UI animations that should keep running while the game is paused.
+    pub real_delta_time: f32,
+    /// Total real time in seconds, advancing by [`Engine::real_delta_time`] every frame.
+    pub real_time: f32,
     pub stdout: io::Stdout,
     pub(crate) default_blending_color: Color,
     pub(crate) fps_counter: FpsCounter,
     pub(crate) max_layer_index: usize,
     pub(crate) frame: FramePair,
     pub(crate) fps_limiter: FpsLimiter,
+    pub(crate) fixed_timestep: Option<FixedTimestep>,
+    pub(crate) time_scale: f32,
+    pub(crate) paused: bool,
+    pub(crate) input: InputState,
+    pub(crate) input_source: InputSource,
+    pub(crate) actions: ActionState,
+    pub(crate) gestures: GestureState,
+    pub(crate) hit_regions: HitRegions,
     pub(crate) particle_state: Vec<ParticleState>,
+    pub(crate) particle_emitters: Vec<EmitterState>,
+    pub(crate) particle_rng: Box<dyn RngCore + Send>,
+    pub(crate) particle_events: Vec<ParticleEvent>,
+    pub(crate) pending_sub_emitters: Vec<PendingSubEmitter>,
+    pub(crate) particle_budget: Option<ParticleBudget>,
+    pub(crate) next_emitter_id: u64,
+    pub(crate) particle_gravity: (f32, f32),
+    pub(crate) cell_aspect_ratio: f32,
+    pub(crate) force_fields: Vec<(ForceFieldHandle, ForceField)>,
+    pub(crate) next_force_field_id: u64,
+    pub(crate) solid_rects: Vec<(SolidHandle, SolidRect)>,
+    pub(crate) next_solid_id: u64,
+    pub(crate) solid_mask: Option<SolidMask>,
+    pub(crate) tweened_values: Vec<(u64, TweenValue)>,
+    pub(crate) next_tweened_id: u64,
+    pub(crate) tweens: Vec<PlayingTween>,
+    pub(crate) next_tween_id: u64,
+    pub(crate) timers: Vec<Timer>,
+    pub(crate) next_timer_id: u64,
+    pub(crate) merge_policy: MergePolicy,
+    pub(crate) headless: bool,
     title: &'static str,
 }
 
@@ -37,13 +104,44 @@ impl Engine {
         Self {
             delta_time: 0.01667,
             game_time: 0.0,
+            real_delta_time: 0.01667,
+            real_time: 0.0,
             title: "my-awesome-terminal",
             stdout: io::stdout(),
             max_layer_index: 0,
             frame: FramePair::new(cols, rows),
             fps_limiter: FpsLimiter::new(60, 0.001, 0.002),
             fps_counter: FpsCounter::new(0.3),
+            fixed_timestep: None,
+            time_scale: 1.0,
+            paused: false,
+            input: InputState::new(),
+            input_source: InputSource::Terminal,
+            actions: ActionState::default(),
+            gestures: GestureState::default(),
+            hit_regions: HitRegions::default(),
             particle_state: Vec::with_capacity(512),
+            particle_emitters: Vec::new(),
+            particle_rng: default_particle_rng(),
+            particle_events: Vec::new(),
+            pending_sub_emitters: Vec::new(),
+            particle_budget: None,
+            next_emitter_id: 0,
+            particle_gravity: (0.0, 200.0),
+            cell_aspect_ratio: 0.5,
+            force_fields: Vec::new(),
+            next_force_field_id: 0,
+            solid_rects: Vec::new(),
+            next_solid_id: 0,
+            solid_mask: None,
+            tweened_values: Vec::new(),
+            next_tweened_id: 0,
+            tweens: Vec::new(),
+            next_tween_id: 0,
+            timers: Vec::new(),
+            next_timer_id: 0,
+            merge_policy: MergePolicy::default(),
+            headless: false,
             default_blending_color: {
                 match termbg::rgb(Duration::from_millis(100)) {
                     Ok(rgb) => Color::new(rgb.r as u8, rgb.g as u8, rgb.b as u8, 255),
@@ -63,6 +161,150 @@ impl Engine {
         fps_limiter::limit_fps(&mut self.fps_limiter, value);
         self
     }
+
+    /// Runs the simulation at a fixed rate of `steps_per_sec`, independent of the frame rate.
+    ///
+    /// A value of `0` disables the fixed timestep. See the [`fixed_timestep`](crate::fixed_timestep) module for details.
+    pub fn fixed_timestep(mut self, steps_per_sec: u32) -> Self {
+        let max_steps_per_frame: u32 = match &self.fixed_timestep {
+            Some(fixed_timestep) => fixed_timestep.max_steps_per_frame,
+            None => 8,
+        };
+        self.fixed_timestep =
+            (steps_per_sec > 0).then(|| FixedTimestep::new(steps_per_sec, max_steps_per_frame));
+        self
+    }
+
+    /// Sets the speed at which game time passes.
+    ///
+    /// See [`set_time_scale`] for details.
+    pub fn time_scale(mut self, value: f32) -> Self {
+        set_time_scale(&mut self, value);
+        self
+    }
+
+    /// Caps the amount of fixed steps taken in a single frame to catch up after a slow frame.
+    ///
+    /// Time beyond the cap is dropped, slowing the simulation down instead of stalling.
+    /// Defaults to `8`. Has no effect unless [`Engine::fixed_timestep`] is enabled.
+    pub fn max_fixed_steps(mut self, value: u32) -> Self {
+        if let Some(fixed_timestep) = &mut self.fixed_timestep {
+            fixed_timestep.max_steps_per_frame = value.max(1);
+        }
+        self
+    }
+
+    /// Enables tracking of held keys, queried using [`input_state`](crate::input::input_state).
+    ///
+    /// When enabled, input events are drained during [`start_frame`] and should be read using
+    /// [`InputState::events`](crate::input::InputState::events) instead of [`poll_input`].
+    pub fn track_input(mut self, value: bool) -> Self {
+        self.input.tracking = value;
+        self
+    }
+
+    /// Requests pixel precise mouse positions (SGR-pixel mode) and enables input tracking.
+    ///
+    /// Only takes effect if the terminal reports its size in pixels. Terminals that don't support
+    /// the mode but report their pixel size will produce wrong mouse positions, so this should only be enabled
+    /// for terminals known to support it, such as kitty, WezTerm, foot or xterm.
+    pub fn pixel_mouse(mut self, value: bool) -> Self {
+        self.input.pixel_mouse_requested = value;
+        self.input.tracking |= value;
+        self
+    }
+
+    /// Sets the action map and enables input tracking.
+    ///
+    /// See [`set_action_map`] for details.
+    pub fn actions(mut self, value: ActionMap) -> Self {
+        set_action_map(&mut self, value);
+        self
+    }
+
+    /// Sets the thresholds used to recognize [gestures](crate::input::gesture).
+    pub fn gesture_config(mut self, value: GestureConfig) -> Self {
+        self.gestures.config = value;
+        self
+    }
+
+    /// Seconds without a key press event after which a held key counts as released,
+    /// on terminals that don't report key releases.
+    ///
+    /// Should be longer than the key repeat delay of the system. Defaults to `0.5`.
+    pub fn key_release_timeout(mut self, value_sec: f32) -> Self {
+        self.input.release_timeout = Duration::from_secs_f32(value_sec.max(0.0));
+        self
+    }
+
+    /// Seeds the random number generator used by the particle system, making particle effects reproducible.
+    ///
+    /// See [`seed_particle_rng`] for details.
+    pub fn particle_seed(mut self, seed: u64) -> Self {
+        seed_particle_rng(&mut self, seed);
+        self
+    }
+
+    /// Caps the amount of alive particles.
+    ///
+    /// See [`set_particle_budget`] for details.
+    pub fn particle_budget(mut self, value: ParticleBudget) -> Self {
+        set_particle_budget(&mut self, Some(value));
+        self
+    }
+
+    /// Sets how the `fg` color of merged octad and blocktad clusters is chosen.
+    ///
+    /// Defaults to [`MergePolicy::LastDrawn`].
+    pub fn merge_policy(mut self, value: MergePolicy) -> Self {
+        self.merge_policy = value;
+        self
+    }
+
+    /// Runs the engine without a terminal.
+    ///
+    /// Frames are still composed, but nothing is written to the terminal and its state is left untouched
+    /// by [`init`] and [`exit_cleanup`]. Mostly useful for replaying recorded input in tests,
+    /// see the [`recording`](crate::input::recording) module.
+    pub fn headless(mut self, value: bool) -> Self {
+        self.headless = value;
+        self
+    }
+}
+
+/// Sets the speed at which game time passes, eg. `0.25` for slow-motion or `2.0` for fast-forward.
+///
+/// Scales [`Engine::delta_time`] and with it [`Engine::game_time`], the particle system, timers and tweens.
+/// [`Engine::real_delta_time`] and [`Engine::real_time`] are unaffected. Takes effect from the next frame on.
+/// Negative values are treated as `0.0`. Defaults to `1.0`.
+pub fn set_time_scale(engine: &mut Engine, value: f32) {
+    engine.time_scale = value.max(0.0);
+}
+
+/// The speed at which game time passes, see [`set_time_scale`].
+pub fn time_scale(engine: &Engine) -> f32 {
+    engine.time_scale
+}
+
+/// Pauses or unpauses game time.
+///
+/// While paused, [`Engine::delta_time`] is `0.0`, so [`Engine::game_time`], the particle system, timers and tweens
+/// stand still. Rendering, input and [`Engine::real_time`] keep going. Takes effect from the next frame on.
+///
+/// # Example
+/// ```rust,no_run
+/// # use germterm::engine::{Engine, is_paused, set_paused};
+/// let mut engine = Engine::new(40, 20);
+/// let paused = is_paused(&engine);
+/// set_paused(&mut engine, !paused);
+/// ```
+pub fn set_paused(engine: &mut Engine, value: bool) {
+    engine.paused = value;
+}
+
+/// Whether game time is paused, see [`set_paused`].
+pub fn is_paused(engine: &Engine) -> bool {
+    engine.paused
 }
 
 /// Overrides the default blending color.
@@ -91,6 +333,9 @@ pub fn init(engine: &mut Engine) -> io::Result<()> {
             .layered_draw_queue
             .resize_with(layer_count, Layer::new);
     }
+    if engine.headless {
+        return Ok(());
+    }
 
     terminal::enable_raw_mode()?;
     execute!(
@@ -100,17 +345,64 @@ pub fn init(engine: &mut Engine) -> io::Result<()> {
         event::EnableMouseCapture,
         cursor::Hide,
     )?;
+
+    // Real key release events are only available through the kitty keyboard protocol
+    if engine.input.tracking && terminal::supports_keyboard_enhancement().unwrap_or(false) {
+        execute!(
+            engine.stdout,
+            event::PushKeyboardEnhancementFlags(
+                event::KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES
+                    | event::KeyboardEnhancementFlags::REPORT_EVENT_TYPES
+            )
+        )?;
+        engine.input.keyboard_enhanced = true;
+    }
+
+    if engine.input.pixel_mouse_requested
+        && let Some(cell_pixel_size) = query_cell_pixel_size()
+    {
+        execute!(engine.stdout, style::Print(ENABLE_PIXEL_MOUSE))?;
+        engine.input.pixel_mouse = true;
+        engine.input.cell_pixel_size = cell_pixel_size;
+    }
     Ok(())
 }
 
+const ENABLE_PIXEL_MOUSE: &str = "\x1b[?1016h";
+const DISABLE_PIXEL_MOUSE: &str = "\x1b[?1016l";
+
 /// Cleans up the terminal state and exits the altenate screen.
 ///
 /// Not calling ['exit_cleanup'] before exiting the program
 /// will result in a messed up terminal state. (Be nice, clean up after yourself!)
 pub fn exit_cleanup(engine: &mut Engine) -> io::Result<()> {
+    if engine.headless {
+        return Ok(());
+    }
+    restore_terminal(
+        &mut engine.stdout,
+        engine.input.keyboard_enhanced,
+        engine.input.pixel_mouse,
+    )?;
+    engine.input.keyboard_enhanced = false;
+    engine.input.pixel_mouse = false;
+    Ok(())
+}
+
+fn restore_terminal(
+    stdout: &mut impl Write,
+    keyboard_enhanced: bool,
+    pixel_mouse: bool,
+) -> io::Result<()> {
+    if keyboard_enhanced {
+        execute!(stdout, event::PopKeyboardEnhancementFlags)?;
+    }
+    if pixel_mouse {
+        execute!(stdout, style::Print(DISABLE_PIXEL_MOUSE))?;
+    }
     terminal::disable_raw_mode()?;
     execute!(
-        engine.stdout,
+        stdout,
         terminal::LeaveAlternateScreen,
         terminal::EnableLineWrap,
         cursor::Show,
@@ -119,14 +411,175 @@ pub fn exit_cleanup(engine: &mut Engine) -> io::Result<()> {
     Ok(())
 }
 
+/// Clears the terminal and redraws the whole frame at the end of the current frame.
+///
+/// Should be called when the terminal is resized, as terminals may clear or garble their contents on resize.
+/// [`run`] calls this automatically.
+///
+/// The frame keeps the size passed to [`Engine::new`], it's not resized along with the terminal.
+/// Cells outside a smaller terminal are cut off until it grows back.
+pub fn handle_resize(engine: &mut Engine) -> io::Result<()> {
+    if !engine.headless {
+        execute!(engine.stdout, terminal::Clear(terminal::ClearType::All))?;
+    }
+    engine.frame.invalidate();
+    Ok(())
+}
+
+/// Runs an [`App`] until it quits, returning its exit value.
+///
+/// Takes care of initializing the engine, polling input, redrawing after resizes and starting and ending frames.
+/// The terminal is always restored before returning, including when an error occurs,
+/// and before the panic message is printed if the app panics.
+///
+/// Layers should be created before calling this function.
+///
+/// See [`App`] for an example.
+pub fn run<A: App>(engine: &mut Engine, mut app: A) -> io::Result<A::Exit> {
+    init(engine)?;
+
+    let result: io::Result<A::Exit> = if engine.headless {
+        run_loop(engine, &mut app)
+    } else {
+        let (keyboard_enhanced, pixel_mouse) =
+            (engine.input.keyboard_enhanced, engine.input.pixel_mouse);
+        let restore = move || {
+            let _ = restore_terminal(&mut io::stdout(), keyboard_enhanced, pixel_mouse);
+        };
+        match catch_unwind_with_hook(restore, || run_loop(engine, &mut app)) {
+            Ok(result) => result,
+            Err(payload) => {
+                // The hook already restored the terminal before the panic message was printed
+                engine.input.keyboard_enhanced = false;
+                engine.input.pixel_mouse = false;
+                panic::resume_unwind(payload);
+            }
+        }
+    };
+
+    let cleanup_result: io::Result<()> = exit_cleanup(engine);
+    let exit: A::Exit = result?;
+    cleanup_result?;
+    Ok(exit)
+}
+
+type PanicHook = Box<dyn Fn(&PanicHookInfo<'_>) + Send + Sync + 'static>;
+
+/// Runs `f`, calling `before_panic` ahead of the current panic hook if it panics.
+///
+/// The previous hook is put back in place before returning, whether `f` returns or panics.
+fn catch_unwind_with_hook<R>(
+    before_panic: impl Fn() + Send + Sync + 'static,
+    f: impl FnOnce() -> R,
+) -> thread::Result<R> {
+    let previous: Arc<PanicHook> = Arc::new(panic::take_hook());
+    let hook: Arc<PanicHook> = previous.clone();
+    panic::set_hook(Box::new(move |info| {
+        before_panic();
+        hook(info);
+    }));
+
+    let result: thread::Result<R> = panic::catch_unwind(AssertUnwindSafe(f));
+
+    // Dropping the installed hook releases its reference to the previous one
+    drop(panic::take_hook());
+    match Arc::try_unwrap(previous) {
+        Ok(previous) => panic::set_hook(previous),
+        Err(previous) => panic::set_hook(Box::new(move |info| previous(info))),
+    }
+    result
+}
+
+fn run_loop<A: App>(engine: &mut Engine, app: &mut A) -> io::Result<A::Exit> {
+    loop {
+        start_frame(engine);
+
+        let events: Vec<Event> = if engine.input.tracking {
+            engine.input.events().to_vec()
+        } else {
+            poll_input().collect()
+        };
+        for event in events {
+            if let Event::Resize(..) = event {
+                handle_resize(engine)?;
+            }
+            app.on_event(engine, &event);
+        }
+
+        for _ in 0..fixed_steps(engine) {
+            app.update(engine);
+        }
+        app.draw(engine);
+
+        end_frame(engine)?;
+
+        if let Some(exit) = app.should_quit() {
+            return Ok(exit);
+        }
+    }
+}
+
 /// Prepares a fresh frame state.
 ///
 /// This function should be called once at the start of each frame inside the update loop.
 ///
 /// Drawing should only happen after this is called for predictable results.
+///
+/// While replaying recorded input, the frame time and input events are taken from the recording
+/// instead of waiting for the next frame and polling the terminal.
 pub fn start_frame(engine: &mut Engine) {
-    engine.delta_time = wait_for_next_frame(&mut engine.fps_limiter);
-    update_fps_counter(&mut engine.fps_counter, engine.delta_time);
+    let frame: RecordedFrame = match next_replay_frame(&mut engine.input_source) {
+        Some(frame) => frame,
+        None => {
+            let delta_time: f32 = wait_for_next_frame(&mut engine.fps_limiter);
+            let events: Vec<Event> = if engine.input.tracking {
+                poll_input().collect()
+            } else {
+                Vec::new()
+            };
+            // Queried here so that it's recorded, keeping replays independent of the terminal
+            let resized: bool = events
+                .iter()
+                .any(|event| matches!(event, Event::Resize(..)));
+            let cell_pixel_size: Option<(f32, f32)> = if engine.input.pixel_mouse && resized {
+                query_cell_pixel_size()
+            } else {
+                None
+            };
+            RecordedFrame {
+                delta_time,
+                events,
+                cell_pixel_size,
+            }
+        }
+    };
+    record_frame(&mut engine.input_source, &frame);
+    let RecordedFrame {
+        delta_time,
+        events,
+        cell_pixel_size,
+    } = frame;
+    engine.real_delta_time = delta_time;
+    engine.real_time += delta_time;
+    engine.delta_time = if engine.paused {
+        0.0
+    } else {
+        delta_time * engine.time_scale
+    };
+    update_fps_counter(&mut engine.fps_counter, engine.real_delta_time);
+    update_input_state(
+        &mut engine.input,
+        engine.real_delta_time,
+        events,
+        cell_pixel_size,
+    );
+    update_action_state(&mut engine.actions, &engine.input);
+    update_gestures(engine);
+    update_tweens(engine);
+    update_timers(engine);
+    if let Some(fixed_timestep) = &mut engine.fixed_timestep {
+        advance_fixed_timestep(fixed_timestep, engine.delta_time);
+    }
 
     let lowest_layer_index: LayerIndex = create_layer(engine, 0);
     erase_rect(
@@ -145,22 +598,139 @@ pub fn start_frame(engine: &mut Engine) {
 ///
 /// No drawing should be happening after this function is called in the update loop.
 pub fn end_frame(engine: &mut Engine) -> io::Result<()> {
-    update_and_draw_particles(engine);
+    engine.particle_events.clear();
+    match &engine.fixed_timestep {
+        Some(fixed_timestep) => {
+            let (step_sec, steps) = (fixed_timestep.step_sec, fixed_timestep.steps);
+            for _ in 0..steps {
+                update_particles(engine, step_sec);
+                engine.game_time += step_sec;
+            }
+        }
+        // Particles stand still while paused
+        None if engine.delta_time > 0.0 => update_particles(engine, engine.delta_time),
+        None => {}
+    }
+    draw_particles(engine, interpolation_alpha(engine));
 
     let height = engine.frame.height;
     let width = engine.frame.width;
-    let (current, layered) = engine.frame.current_mut_and_layered_mut();
+    let (current, layered, merge_sums) = engine.frame.compose_targets_mut();
     compose_frame_buffer(
         current,
         layered.iter_mut().flat_map(|v| v.0.drain(..)),
+        merge_sums,
         width,
         height,
         engine.default_blending_color,
+        engine.merge_policy,
     );
-    let diff_products = engine.frame.diff();
-    draw_to_terminal(&mut engine.stdout, diff_products)?;
+    if !engine.headless {
+        let diff_products = engine.frame.diff();
+        draw_to_terminal(&mut engine.stdout, diff_products)?;
+    }
     engine.frame.swap_frames();
+    swap_hit_regions(&mut engine.hit_regions);
 
-    engine.game_time += engine.delta_time;
+    if engine.fixed_timestep.is_none() {
+        engine.game_time += engine.delta_time;
+    }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{
+        Mutex,
+        atomic::{AtomicUsize, Ordering},
+    };
+
+    #[test]
+    fn time_scale_and_pausing_only_affect_game_time() {
+        use crate::input::recording::{InputRecording, RecordedFrame, start_replay};
+
+        let mut engine = Engine::new(40, 20).headless(true);
+        init(&mut engine).unwrap();
+        let frame = RecordedFrame {
+            delta_time: 0.25,
+            ..Default::default()
+        };
+        start_replay(
+            &mut engine,
+            InputRecording {
+                frames: vec![frame; 3],
+                ..Default::default()
+            },
+        );
+
+        set_time_scale(&mut engine, 0.5);
+        start_frame(&mut engine);
+        assert_eq!(engine.delta_time, 0.125);
+        assert_eq!(engine.real_delta_time, 0.25);
+        assert_eq!(engine.real_time, 0.25);
+        end_frame(&mut engine).unwrap();
+        assert_eq!(engine.game_time, 0.125);
+
+        set_paused(&mut engine, true);
+        start_frame(&mut engine);
+        assert_eq!(engine.delta_time, 0.0);
+        assert_eq!(engine.real_delta_time, 0.25);
+        assert_eq!(engine.real_time, 0.5);
+        end_frame(&mut engine).unwrap();
+        assert_eq!(engine.game_time, 0.125);
+
+        set_paused(&mut engine, false);
+        set_time_scale(&mut engine, 2.0);
+        start_frame(&mut engine);
+        assert_eq!(engine.delta_time, 0.5);
+        assert_eq!(engine.real_time, 0.75);
+        end_frame(&mut engine).unwrap();
+        assert_eq!(engine.game_time, 0.625);
+    }
+
+    #[test]
+    fn panic_hooks_are_restored_on_return_and_on_unwind() {
+        static PREVIOUS_CALLS: AtomicUsize = AtomicUsize::new(0);
+        static BEFORE_PANIC_CALLS: AtomicUsize = AtomicUsize::new(0);
+        // Hooks are global, so only panics of this thread are counted
+        static TEST_THREAD: Mutex<Option<thread::ThreadId>> = Mutex::new(None);
+        let on_test_thread = || *TEST_THREAD.lock().unwrap() == Some(thread::current().id());
+
+        *TEST_THREAD.lock().unwrap() = Some(thread::current().id());
+        let default_hook: Arc<PanicHook> = Arc::new(panic::take_hook());
+        let fallback: Arc<PanicHook> = default_hook.clone();
+        panic::set_hook(Box::new(move |info| {
+            if on_test_thread() {
+                PREVIOUS_CALLS.fetch_add(1, Ordering::SeqCst);
+            } else {
+                fallback(info);
+            }
+        }));
+        let before_panic = move || {
+            if on_test_thread() {
+                BEFORE_PANIC_CALLS.fetch_add(1, Ordering::SeqCst);
+            }
+        };
+
+        assert_eq!(catch_unwind_with_hook(before_panic, || 7).ok(), Some(7));
+        assert!(panic::catch_unwind(|| panic!("after return")).is_err());
+        assert_eq!(PREVIOUS_CALLS.load(Ordering::SeqCst), 1);
+        assert_eq!(BEFORE_PANIC_CALLS.load(Ordering::SeqCst), 0);
+
+        let result = catch_unwind_with_hook(before_panic, || panic!("inside"));
+        assert!(result.is_err());
+        assert_eq!(PREVIOUS_CALLS.load(Ordering::SeqCst), 2);
+        assert_eq!(BEFORE_PANIC_CALLS.load(Ordering::SeqCst), 1);
+
+        assert!(panic::catch_unwind(|| panic!("after unwind")).is_err());
+        assert_eq!(PREVIOUS_CALLS.load(Ordering::SeqCst), 3);
+        assert_eq!(BEFORE_PANIC_CALLS.load(Ordering::SeqCst), 1);
+
+        drop(panic::take_hook());
+        *TEST_THREAD.lock().unwrap() = None;
+        if let Ok(default_hook) = Arc::try_unwrap(default_hook) {
+            panic::set_hook(default_hook);
+        }
+    }
+}
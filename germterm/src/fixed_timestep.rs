@@ -0,0 +1,160 @@
+//! Fixed-timestep simulation.
+//!
+//! By default, simulation runs once per rendered frame using the variable [`Engine::delta_time`],
+//! which ties the simulation rate to the render rate. Enabling a fixed timestep using
+//! [`Engine::fixed_timestep`] decouples the two: every frame, the elapsed time is accumulated
+//! and consumed in steps of a constant size.
+//!
+//! Game logic should then run [`fixed_steps`] times per frame using [`fixed_delta_time`],
+//! and rendering can smooth out movement by interpolating between the previous and the current
+//! state using [`interpolation_alpha`].
+//!
+//! The particle system steps on the fixed clock as well when it's enabled.
+//!
+//! # Example
+//! ```rust,no_run
+//! # use germterm::{engine::{Engine, start_frame, end_frame}, fixed_timestep::{fixed_steps, fixed_delta_time, interpolation_alpha, interpolate}};
+//! let mut engine = Engine::new(40, 20).fixed_timestep(50);
+//! let (mut prev_x, mut x) = (0.0, 0.0);
+//!
+//! loop {
+//!     start_frame(&mut engine);
+//!
+//!     for _ in 0..fixed_steps(&engine) {
+//!         prev_x = x;
+//!         x += 10.0 * fixed_delta_time(&engine);
+//!     }
+//!
+//!     let draw_x = interpolate(prev_x, x, interpolation_alpha(&engine));
+//!     // Draw at `draw_x`...
+//!
+//!     end_frame(&mut engine).unwrap();
+//! }
+//! ```
+
+use crate::engine::Engine;
+
+pub(crate) struct FixedTimestep {
+    pub(crate) step_sec: f32,
+    pub(crate) max_steps_per_frame: u32,
+    pub(crate) steps: u32,
+    accumulator_sec: f32,
+}
+
+impl FixedTimestep {
+    pub fn new(steps_per_sec: u32, max_steps_per_frame: u32) -> Self {
+        Self {
+            step_sec: 1.0 / steps_per_sec as f32,
+            max_steps_per_frame,
+            steps: 0,
+            accumulator_sec: 0.0,
+        }
+    }
+}
+
+/// Accumulates `delta_time` and computes the amount of fixed steps to run this frame.
+pub(crate) fn advance_fixed_timestep(fixed_timestep: &mut FixedTimestep, delta_time: f32) {
+    fixed_timestep.accumulator_sec += delta_time.max(0.0);
+
+    let steps: u32 = (fixed_timestep.accumulator_sec / fixed_timestep.step_sec) as u32;
+    fixed_timestep.accumulator_sec -= steps as f32 * fixed_timestep.step_sec;
+
+    if steps > fixed_timestep.max_steps_per_frame {
+        // Too far behind to catch up, drop the backlog instead of spiraling
+        fixed_timestep.steps = fixed_timestep.max_steps_per_frame;
+        fixed_timestep.accumulator_sec %= fixed_timestep.step_sec;
+    } else {
+        fixed_timestep.steps = steps;
+    }
+}
+
+/// Amount of fixed steps to simulate during the current frame.
+///
+/// Always `1` when the fixed timestep is disabled.
+pub fn fixed_steps(engine: &Engine) -> u32 {
+    match &engine.fixed_timestep {
+        Some(fixed_timestep) => fixed_timestep.steps,
+        None => 1,
+    }
+}
+
+/// Duration of a single fixed step in seconds.
+///
+/// Equal to [`Engine::delta_time`] when the fixed timestep is disabled.
+pub fn fixed_delta_time(engine: &Engine) -> f32 {
+    match &engine.fixed_timestep {
+        Some(fixed_timestep) => fixed_timestep.step_sec,
+        None => engine.delta_time,
+    }
+}
+
+/// How far the current frame is between the last and the next fixed step, in the range `0.0..1.0`.
+///
+/// Always `1.0` when the fixed timestep is disabled, meaning the current state is drawn as is.
+pub fn interpolation_alpha(engine: &Engine) -> f32 {
+    match &engine.fixed_timestep {
+        Some(fixed_timestep) => {
+            (fixed_timestep.accumulator_sec / fixed_timestep.step_sec).clamp(0.0, 1.0)
+        }
+        None => 1.0,
+    }
+}
+
+/// Linearly interpolates between the `previous` and the `current` value of a fixed step.
+///
+/// Meant to be used with [`interpolation_alpha`].
+#[inline]
+pub fn interpolate(previous: f32, current: f32, alpha: f32) -> f32 {
+    previous + (current - previous) * alpha
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accumulates_partial_steps() {
+        let mut fixed_timestep = FixedTimestep::new(50, 8);
+        let mut total_steps: u32 = 0;
+        for _ in 0..11 {
+            advance_fixed_timestep(&mut fixed_timestep, 0.005);
+            total_steps += fixed_timestep.steps;
+        }
+        assert_eq!(total_steps, 2);
+        assert!((fixed_timestep.accumulator_sec - 0.015).abs() < 1e-5);
+    }
+
+    #[test]
+    fn runs_several_steps_after_a_slow_frame() {
+        let mut fixed_timestep = FixedTimestep::new(50, 8);
+        advance_fixed_timestep(&mut fixed_timestep, 0.07);
+        assert_eq!(fixed_timestep.steps, 3);
+        assert!((fixed_timestep.accumulator_sec - 0.01).abs() < 1e-5);
+    }
+
+    #[test]
+    fn drops_the_backlog_beyond_the_step_cap() {
+        let mut fixed_timestep = FixedTimestep::new(50, 4);
+        advance_fixed_timestep(&mut fixed_timestep, 1.01);
+        assert_eq!(fixed_timestep.steps, 4);
+        assert!(fixed_timestep.accumulator_sec < fixed_timestep.step_sec);
+
+        advance_fixed_timestep(&mut fixed_timestep, 0.0);
+        assert_eq!(fixed_timestep.steps, 0);
+    }
+
+    #[test]
+    fn ignores_negative_delta_time() {
+        let mut fixed_timestep = FixedTimestep::new(50, 8);
+        advance_fixed_timestep(&mut fixed_timestep, -1.0);
+        assert_eq!(fixed_timestep.steps, 0);
+        assert_eq!(fixed_timestep.accumulator_sec, 0.0);
+    }
+
+    #[test]
+    fn interpolates_between_steps() {
+        assert_eq!(interpolate(2.0, 4.0, 0.0), 2.0);
+        assert_eq!(interpolate(2.0, 4.0, 0.25), 2.5);
+        assert_eq!(interpolate(2.0, 4.0, 1.0), 4.0);
+    }
+}
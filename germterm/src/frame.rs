@@ -1,6 +1,6 @@
 use crate::{
     cell::{Cell, CellFormat},
-    color::{Color, blend_source_over},
+    color::{Color, blend_source_over, lerp, luminance},
     draw::BLOCKTAD_CHAR_LUT,
     layer::Layer,
     rich_text::{Attributes, RichText},
@@ -19,6 +19,56 @@ pub struct DrawCall {
     pub y: i16,
 }
 
+/// Decides which `fg` color a merged octad or blocktad cluster ends up with.
+///
+/// Merged clusters can only display a single `fg` color, so the compositor has to pick one
+/// when multiple octads or blocktads are drawn to the same cell.
+///
+/// The policy is set on the [`Engine`](crate::engine::Engine) using [`Engine::merge_policy`](crate::engine::Engine::merge_policy).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MergePolicy {
+    /// The merged cluster takes the `fg` color of the last drawn dot.
+    #[default]
+    LastDrawn,
+    /// Every draw contributing to the cluster has an equal share of the final `fg` color.
+    Average,
+    /// Every draw contributes to the final `fg` color proportionally to the amount of dots it adds.
+    DotWeighted,
+    /// The merged cluster takes the brightest `fg` color out of all contributing draws.
+    Brightest,
+}
+
+/// Sums of the `fg` channels of all draws merged into a cell, used by [`MergePolicy::Average`].
+///
+/// Averaging the sums instead of blending each draw into the previous result avoids accumulating rounding errors.
+#[derive(Clone, Copy, Default)]
+pub(crate) struct MergeSum {
+    count: u32,
+    rgba: [u64; 4],
+}
+
+impl MergeSum {
+    fn new(color: Color) -> Self {
+        let mut merge_sum: MergeSum = MergeSum::default();
+        merge_sum.add(color);
+        merge_sum
+    }
+
+    fn add(&mut self, color: Color) {
+        let (r, g, b, a) = color.rgba();
+        self.count += 1;
+        for (sum, channel) in self.rgba.iter_mut().zip([r, g, b, a]) {
+            *sum += channel as u64;
+        }
+    }
+
+    fn average(&self) -> Color {
+        let count: u64 = self.count.max(1) as u64;
+        let [r, g, b, a] = self.rgba.map(|sum| ((sum + count / 2) / count) as u8);
+        Color::new(r, g, b, a)
+    }
+}
+
 pub struct DiffProduct<'a> {
     pub cell: &'a Cell,
     pub x: u16,
@@ -64,6 +114,8 @@ pub struct FramePair {
     pub(crate) width: u16,
     pub(crate) height: u16,
     pub(crate) layered_draw_queue: Vec<Layer>,
+    /// Draws merged into each cell of the current frame, used by [`MergePolicy::Average`].
+    pub(crate) merge_sums: Vec<MergeSum>,
 }
 
 impl FramePair {
@@ -74,6 +126,7 @@ impl FramePair {
             width,
             height,
             layered_draw_queue: vec![],
+            merge_sums: vec![MergeSum::default(); width as usize * height as usize],
         }
     }
 
@@ -116,19 +169,43 @@ impl FramePair {
         };
     }
 
+    /// Makes every cell of the old frame differ from any composed cell,
+    /// so the next diff redraws the whole frame.
+    pub fn invalidate(&mut self) {
+        let old: usize = 1 - self.order as usize;
+        for pair in self.frames.chunks_exact_mut(2) {
+            pair[old] = Cell {
+                ch: '\0',
+                ..Cell::EMPTY
+            };
+        }
+    }
+
     pub fn current_mut_and_layered_mut(&mut self) -> (FrameMut<'_>, &mut Vec<Layer>) {
         let frame = FrameMut(&mut self.frames, self.order as usize);
         let layers = &mut self.layered_draw_queue;
         (frame, layers)
     }
+
+    /// Like [`FramePair::current_mut_and_layered_mut`], also borrowing the merge sums used while composing.
+    pub(crate) fn compose_targets_mut(
+        &mut self,
+    ) -> (FrameMut<'_>, &mut Vec<Layer>, &mut [MergeSum]) {
+        let frame = FrameMut(&mut self.frames, self.order as usize);
+        let layers = &mut self.layered_draw_queue;
+        let merge_sums = &mut self.merge_sums;
+        (frame, layers, merge_sums)
+    }
 }
 
 pub(crate) fn compose_frame_buffer(
     mut buffer: FrameMut<'_>,
     draw_queue: impl Iterator<Item = DrawCall>,
+    merge_sums: &mut [MergeSum],
     cols: u16,
     rows: u16,
     default_blending_color: Color,
+    merge_policy: MergePolicy,
 ) {
     let (cols, rows) = (cols as i16, rows as i16);
 
@@ -170,7 +247,29 @@ pub(crate) fn compose_frame_buffer(
                 format: draw_call.rich_text.cell_format,
             };
 
-            buffer[cell_index] = compose_cell(old_cell, new_cell, default_blending_color);
+            let mut composed_cell: Cell = compose_cell(old_cell, new_cell, default_blending_color);
+
+            if merge_policy != MergePolicy::LastDrawn {
+                let merge_sum: &mut MergeSum = &mut merge_sums[cell_index];
+                if is_dot_merge(&old_cell, &new_cell) {
+                    // Cells that weren't drawn during this frame count as a single draw
+                    if merge_sum.count == 0 {
+                        merge_sum.add(old_cell.fg);
+                    }
+                    merge_sum.add(composed_cell.fg);
+                    composed_cell.fg = merge_fg(
+                        merge_policy,
+                        &old_cell,
+                        &new_cell,
+                        composed_cell.fg,
+                        merge_sum,
+                    );
+                } else {
+                    *merge_sum = MergeSum::new(composed_cell.fg);
+                }
+            }
+
+            buffer[cell_index] = composed_cell;
         }
     }
 }
@@ -252,11 +351,16 @@ fn compose_cell(old: Cell, new: Cell, default_blending_color: Color) -> Cell {
     let both_ch_equal: bool = old.ch == new.ch;
 
     // Cell format related
-    let new_twoxel: bool = new.format == CellFormat::Twoxel;
+    // Vertical and horizontal twoxels share the same merging rules,
+    // but only merge with twoxels of the same orientation
+    let new_twoxel: bool = matches!(
+        new.format,
+        CellFormat::Twoxel | CellFormat::HorizontalTwoxel
+    );
     let new_octad: bool = new.format == CellFormat::Octad;
     let new_blocktad: bool = new.format == CellFormat::Blocktad;
 
-    let old_twoxel: bool = old.format == CellFormat::Twoxel;
+    let old_twoxel: bool = new_twoxel && old.format == new.format;
     let old_octad: bool = old.format == CellFormat::Octad;
     let old_blocktad: bool = old.format == CellFormat::Blocktad;
 
@@ -401,25 +505,291 @@ fn compose_cell(old: Cell, new: Cell, default_blending_color: Color) -> Cell {
     }
 }
 
+/// Whether composing `new` on top of `old` merges two octads or two blocktads into a single cluster.
 #[inline]
-fn merge_octad(a: char, b: char) -> char {
-    let mask_a = (a as u32) - 0x2800;
-    let mask_b = (b as u32) - 0x2800;
-    std::char::from_u32(0x2800 + (mask_a | mask_b)).unwrap()
+fn is_dot_merge(old: &Cell, new: &Cell) -> bool {
+    let same_dot_format: bool =
+        old.format == new.format && matches!(new.format, CellFormat::Octad | CellFormat::Blocktad);
+
+    same_dot_format && !new.attributes.contains(Attributes::NO_FG_COLOR)
 }
 
+/// Picks the `fg` color of a merged octad or blocktad cluster based on the [`MergePolicy`].
+///
+/// `composed_fg` is the `fg` color the cluster would end up with using [`MergePolicy::LastDrawn`],
+/// `merge_sum` already includes it.
 #[inline]
-fn merge_blocktad(a: char, b: char) -> char {
-    let mask_a = BLOCKTAD_CHAR_LUT
-        .iter()
-        .position(|&c| c == a)
-        .expect("char not in BLOCKTAD LUT") as u8;
-    let mask_b = BLOCKTAD_CHAR_LUT
+fn merge_fg(
+    merge_policy: MergePolicy,
+    old: &Cell,
+    new: &Cell,
+    composed_fg: Color,
+    merge_sum: &MergeSum,
+) -> Color {
+    match merge_policy {
+        MergePolicy::LastDrawn => composed_fg,
+        MergePolicy::Average => merge_sum.average(),
+        MergePolicy::DotWeighted => {
+            let (old_dots, new_dots) = match new.format {
+                CellFormat::Blocktad => (
+                    blocktad_mask(old.ch).count_ones(),
+                    blocktad_mask(new.ch).count_ones(),
+                ),
+                _ => (
+                    octad_mask(old.ch).count_ones(),
+                    octad_mask(new.ch).count_ones(),
+                ),
+            };
+            let total_dots: u32 = old_dots + new_dots;
+
+            if total_dots == 0 {
+                composed_fg
+            } else {
+                lerp(old.fg, composed_fg, new_dots as f32 / total_dots as f32)
+            }
+        }
+        MergePolicy::Brightest => {
+            if luminance(composed_fg) >= luminance(old.fg) {
+                composed_fg
+            } else {
+                old.fg
+            }
+        }
+    }
+}
+
+#[inline]
+fn octad_mask(ch: char) -> u8 {
+    ((ch as u32) - 0x2800) as u8
+}
+
+#[inline]
+fn blocktad_mask(ch: char) -> u8 {
+    BLOCKTAD_CHAR_LUT
         .iter()
-        .position(|&c| c == b)
-        .expect("char not in BLOCKTAD LUT") as u8;
+        .position(|&c| c == ch)
+        .expect("char not in BLOCKTAD LUT") as u8
+}
 
-    let merged_mask = mask_a | mask_b;
+#[inline]
+fn merge_octad(a: char, b: char) -> char {
+    let merged_mask = octad_mask(a) | octad_mask(b);
+    std::char::from_u32(0x2800 + merged_mask as u32).unwrap()
+}
+
+#[inline]
+fn merge_blocktad(a: char, b: char) -> char {
+    let merged_mask = blocktad_mask(a) | blocktad_mask(b);
 
     BLOCKTAD_CHAR_LUT[merged_mask as usize]
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn octad(dots: u8, color: Color) -> DrawCall {
+        DrawCall {
+            rich_text: RichText::new(std::char::from_u32(0x2800 + dots as u32).unwrap())
+                .with_fg(color)
+                .with_cell_format(CellFormat::Octad),
+            x: 0,
+            y: 0,
+        }
+    }
+
+    fn twoxel(ch: char, format: CellFormat, color: Color) -> DrawCall {
+        DrawCall {
+            rich_text: RichText::new(ch).with_fg(color).with_cell_format(format),
+            x: 0,
+            y: 0,
+        }
+    }
+
+    /// A space with an opaque `bg`, like an erased cell.
+    fn background(color: Color) -> DrawCall {
+        DrawCall {
+            rich_text: RichText::new(' ').with_bg(color),
+            x: 0,
+            y: 0,
+        }
+    }
+
+    fn compose_single_cell(draws: Vec<DrawCall>, merge_policy: MergePolicy) -> Cell {
+        let mut frames: FramePair = FramePair::new(1, 1);
+        let (current, _, merge_sums) = frames.compose_targets_mut();
+        compose_frame_buffer(
+            current,
+            draws.into_iter(),
+            merge_sums,
+            1,
+            1,
+            Color::BLACK,
+            merge_policy,
+        );
+        frames.current()[0]
+    }
+
+    fn assert_close(color: Color, (r, g, b): (u8, u8, u8)) {
+        let close = |a: u8, b: u8| a.abs_diff(b) <= 2;
+        assert!(
+            close(color.r(), r) && close(color.g(), g) && close(color.b(), b),
+            "{:?} is not close to {:?}",
+            (color.r(), color.g(), color.b()),
+            (r, g, b)
+        );
+    }
+
+    #[test]
+    fn last_drawn_takes_the_last_color() {
+        let cell = compose_single_cell(
+            vec![octad(0b01, Color::RED), octad(0b10, Color::BLUE)],
+            MergePolicy::LastDrawn,
+        );
+        assert_eq!(cell.ch, '\u{2803}');
+        assert_eq!(cell.fg, Color::BLUE);
+    }
+
+    #[test]
+    fn average_gives_every_draw_an_equal_share() {
+        let cell = compose_single_cell(
+            vec![
+                octad(0b001, Color::RED),
+                octad(0b010, Color::GREEN),
+                octad(0b100, Color::BLUE),
+            ],
+            MergePolicy::Average,
+        );
+        assert_eq!(cell.ch, '\u{2807}');
+        assert_close(cell.fg, (85, 85, 85));
+    }
+
+    #[test]
+    fn average_stays_exact_over_many_merges() {
+        let colors: [(u8, u8, u8); 6] = [
+            (255, 0, 0),
+            (0, 255, 0),
+            (0, 0, 255),
+            (255, 255, 255),
+            (10, 20, 30),
+            (101, 0, 0),
+        ];
+        let draws: Vec<DrawCall> = colors
+            .iter()
+            .enumerate()
+            .map(|(i, &(r, g, b))| octad(1 << i, Color::new(r, g, b, 255)))
+            .collect();
+        let cell = compose_single_cell(draws, MergePolicy::Average);
+        assert_eq!(cell.ch, '\u{283f}');
+        // (621 / 6, 530 / 6, 540 / 6), rounded
+        assert_eq!(cell.fg.rgba(), (104, 88, 90, 255));
+
+        // Alternating between two colors lands exactly between them, however many draws there are
+        let draws: Vec<DrawCall> = (0..200)
+            .map(|i| {
+                let color = if i % 2 == 0 { Color::RED } else { Color::BLUE };
+                octad(1 << (i % 8), color)
+            })
+            .collect();
+        let cell = compose_single_cell(draws, MergePolicy::Average);
+        assert_eq!(cell.fg.rgba(), (128, 0, 128, 255));
+    }
+
+    #[test]
+    fn dot_weighted_weights_by_added_dots() {
+        let cell = compose_single_cell(
+            vec![octad(0b0111, Color::RED), octad(0b1000, Color::BLUE)],
+            MergePolicy::DotWeighted,
+        );
+        assert_close(cell.fg, (191, 0, 64));
+    }
+
+    #[test]
+    fn brightest_keeps_the_brightest_color() {
+        let cell = compose_single_cell(
+            vec![octad(0b01, Color::WHITE), octad(0b10, Color::BLUE)],
+            MergePolicy::Brightest,
+        );
+        assert_eq!(cell.fg, Color::WHITE);
+    }
+
+    #[test]
+    fn average_restarts_after_a_non_octad_draw() {
+        let cell = compose_single_cell(
+            vec![
+                octad(0b01, Color::RED),
+                octad(0b10, Color::RED),
+                DrawCall {
+                    rich_text: RichText::new("x").with_fg(Color::GREEN),
+                    x: 0,
+                    y: 0,
+                },
+                octad(0b01, Color::GREEN),
+                octad(0b10, Color::BLUE),
+            ],
+            MergePolicy::Average,
+        );
+        assert_close(cell.fg, (0, 128, 128));
+    }
+
+    #[test]
+    fn opposing_horizontal_twoxels_merge_into_independent_halves() {
+        let cell = compose_single_cell(
+            vec![
+                background(Color::BLACK),
+                twoxel('▌', CellFormat::HorizontalTwoxel, Color::RED),
+                twoxel('▐', CellFormat::HorizontalTwoxel, Color::CYAN),
+            ],
+            MergePolicy::LastDrawn,
+        );
+        assert!(cell.format == CellFormat::HorizontalTwoxel);
+        assert_eq!(cell.ch, '▌');
+        assert_close(cell.fg, (255, 0, 0));
+        assert_close(cell.bg, (0, 255, 255));
+    }
+
+    #[test]
+    fn horizontal_twoxels_on_the_same_half_replace_its_color() {
+        let cell = compose_single_cell(
+            vec![
+                background(Color::BLACK),
+                twoxel('▐', CellFormat::HorizontalTwoxel, Color::RED),
+                twoxel('▐', CellFormat::HorizontalTwoxel, Color::CYAN),
+            ],
+            MergePolicy::LastDrawn,
+        );
+        assert!(cell.format == CellFormat::HorizontalTwoxel);
+        assert_eq!(cell.ch, '▐');
+        assert_close(cell.fg, (0, 255, 255));
+        assert_close(cell.bg, (0, 0, 0));
+    }
+
+    #[test]
+    fn twoxels_of_different_orientations_dont_merge() {
+        let cell = compose_single_cell(
+            vec![
+                background(Color::BLACK),
+                twoxel('▀', CellFormat::Twoxel, Color::RED),
+                twoxel('▐', CellFormat::HorizontalTwoxel, Color::CYAN),
+            ],
+            MergePolicy::LastDrawn,
+        );
+        assert!(cell.format == CellFormat::HorizontalTwoxel);
+        assert_eq!(cell.ch, '▐');
+        assert_close(cell.fg, (0, 255, 255));
+        assert_close(cell.bg, (0, 0, 0));
+
+        let cell = compose_single_cell(
+            vec![
+                background(Color::BLACK),
+                twoxel('▌', CellFormat::HorizontalTwoxel, Color::RED),
+                twoxel('▄', CellFormat::Twoxel, Color::CYAN),
+            ],
+            MergePolicy::LastDrawn,
+        );
+        assert!(cell.format == CellFormat::Twoxel);
+        assert_eq!(cell.ch, '▄');
+        assert_close(cell.fg, (0, 255, 255));
+        assert_close(cell.bg, (0, 0, 0));
+    }
+}
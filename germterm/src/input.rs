@@ -1,7 +1,35 @@
 //! Input handling.
+//!
+//! Raw input events can be read using [`poll_input`]. For querying which keys are held,
+//! enable input tracking with [`Engine::track_input`] and use [`input_state`].
+//!
+//! Named actions bound to keys, mouse buttons and scrolling are provided by the [`action`] module,
+//! the [`hit_region`] module allows testing which drawn element is under the mouse,
+//! and the [`gesture`] module recognizes clicks, drags and long presses.
+//! Input can be recorded to a file and replayed deterministically using the [`recording`] module.
+//!
+//! ## Mouse coordinates
+//! Terminals report the mouse position in cells by default. Enabling [`Engine::pixel_mouse`] requests
+//! pixel precise reports (SGR-pixel mode), which are converted into fractional cell coordinates
+//! matching the coordinates used by sub-cell drawing functions such as [`draw_octad`](crate::draw::draw_octad).
 
-use crossterm::event::{self, Event};
-use std::time::Duration;
+pub mod action;
+pub mod gesture;
+pub mod hit_region;
+pub mod recording;
+
+use crate::engine::Engine;
+use crossterm::{
+    event::{
+        self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, MediaKeyCode, ModifierKeyCode,
+        MouseButton, MouseEvent, MouseEventKind,
+    },
+    terminal,
+};
+use std::{
+    collections::{HashMap, HashSet},
+    time::Duration,
+};
 
 /// Polls the terminal for input events and drains all available events.
 ///
@@ -30,3 +58,576 @@ pub fn poll_input() -> impl Iterator<Item = Event> {
         }
     })
 }
+
+/// A direction of mouse wheel scrolling.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ScrollDirection {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+/// Keyboard and mouse button state tracked across frames.
+///
+/// Tracking is opt-in using [`Engine::track_input`]. When enabled, [`start_frame`](crate::engine::start_frame)
+/// drains all pending input events and updates the state, so the events should be read using
+/// [`InputState::events`] instead of [`poll_input`].
+///
+/// Key releases are reported by terminals supporting the kitty keyboard protocol.
+/// On other terminals, a held key is only known from its repeated press events, so a key counts as released
+/// once no press event arrived for [`Engine::key_release_timeout`] seconds.
+/// Pressing another key doesn't release held keys, so keys pressed together, such as W and D for a diagonal,
+/// count as held together. Terminals only repeat the most recently pressed key though,
+/// so the other keys are released once the timeout passes without a new press.
+///
+/// Character keys are tracked case-insensitively.
+///
+/// # Example
+/// ```rust,no_run
+/// # use germterm::{crossterm::event::KeyCode, engine::{Engine, start_frame}, input::input_state};
+/// let mut engine = Engine::new(40, 20).track_input(true);
+///
+/// start_frame(&mut engine);
+/// let input = input_state(&engine);
+/// if input.is_down(KeyCode::Char('w')) {
+///     // Move up
+/// }
+/// if input.just_pressed(KeyCode::Char(' ')) {
+///     // Jump
+/// }
+/// ```
+pub struct InputState {
+    pub(crate) tracking: bool,
+    pub(crate) keyboard_enhanced: bool,
+    pub(crate) release_timeout: Duration,
+    events: Vec<Event>,
+    // Seconds of accumulated `delta_time`, used instead of the system clock so that replays are deterministic
+    clock_sec: f64,
+    // Held keys along with the clock time of their last press or repeat event
+    keys_down: HashMap<KeyCode, f64>,
+    just_pressed: HashSet<KeyCode>,
+    just_released: HashSet<KeyCode>,
+    modifiers: KeyModifiers,
+    buttons_down: HashSet<MouseButton>,
+    buttons_just_pressed: HashSet<MouseButton>,
+    buttons_just_released: HashSet<MouseButton>,
+    scrolled: HashSet<ScrollDirection>,
+    scroll_delta: (i32, i32),
+    mouse_position: Option<(f32, f32)>,
+    // Mouse events of the current frame along with their fractional position
+    pub(crate) mouse_events: Vec<(MouseEventKind, (f32, f32))>,
+    drag_starts: HashMap<MouseButton, (f32, f32)>,
+    pub(crate) pixel_mouse_requested: bool,
+    pub(crate) pixel_mouse: bool,
+    pub(crate) cell_pixel_size: (f32, f32),
+}
+
+impl InputState {
+    pub(crate) fn new() -> Self {
+        Self {
+            tracking: false,
+            keyboard_enhanced: false,
+            release_timeout: Duration::from_millis(500),
+            events: Vec::new(),
+            clock_sec: 0.0,
+            keys_down: HashMap::new(),
+            just_pressed: HashSet::new(),
+            just_released: HashSet::new(),
+            modifiers: KeyModifiers::NONE,
+            buttons_down: HashSet::new(),
+            buttons_just_pressed: HashSet::new(),
+            buttons_just_released: HashSet::new(),
+            scrolled: HashSet::new(),
+            scroll_delta: (0, 0),
+            mouse_position: None,
+            mouse_events: Vec::new(),
+            drag_starts: HashMap::new(),
+            pixel_mouse_requested: false,
+            pixel_mouse: false,
+            cell_pixel_size: (1.0, 1.0),
+        }
+    }
+
+    /// Whether `key` is currently held down.
+    pub fn is_down(&self, key: KeyCode) -> bool {
+        self.keys_down.contains_key(&normalize_key(key))
+    }
+
+    /// Whether `key` was pressed since the previous frame.
+    pub fn just_pressed(&self, key: KeyCode) -> bool {
+        self.just_pressed.contains(&normalize_key(key))
+    }
+
+    /// Whether `key` was released since the previous frame.
+    pub fn just_released(&self, key: KeyCode) -> bool {
+        self.just_released.contains(&normalize_key(key))
+    }
+
+    /// Modifiers held during the most recent key or mouse event.
+    pub fn modifiers(&self) -> KeyModifiers {
+        self.modifiers
+    }
+
+    /// Whether the mouse `button` is currently held down.
+    pub fn is_button_down(&self, button: MouseButton) -> bool {
+        self.buttons_down.contains(&button)
+    }
+
+    /// Whether the mouse `button` was pressed since the previous frame.
+    pub fn button_just_pressed(&self, button: MouseButton) -> bool {
+        self.buttons_just_pressed.contains(&button)
+    }
+
+    /// Whether the mouse `button` was released since the previous frame.
+    pub fn button_just_released(&self, button: MouseButton) -> bool {
+        self.buttons_just_released.contains(&button)
+    }
+
+    /// Whether the mouse wheel was scrolled in `direction` since the previous frame.
+    pub fn scrolled(&self, direction: ScrollDirection) -> bool {
+        self.scrolled.contains(&direction)
+    }
+
+    /// Amount of mouse wheel steps scrolled since the previous frame as `(x, y)`,
+    /// with positive values pointing right and down.
+    pub fn scroll_delta(&self) -> (i32, i32) {
+        self.scroll_delta
+    }
+
+    /// Position of the mouse in fractional cells, or `None` before the first mouse event.
+    ///
+    /// Without [`Engine::pixel_mouse`], this is the center of the hovered cell.
+    pub fn mouse_position(&self) -> Option<(f32, f32)> {
+        self.mouse_position
+    }
+
+    /// The cell the mouse is over, or `None` before the first mouse event.
+    pub fn mouse_cell(&self) -> Option<(u16, u16)> {
+        self.mouse_position
+            .map(|(x, y)| (x.max(0.0) as u16, y.max(0.0) as u16))
+    }
+
+    /// Position at which `button` was pressed, while it's held and during the frame it's released.
+    pub fn drag_start(&self, button: MouseButton) -> Option<(f32, f32)> {
+        self.drag_starts.get(&button).copied()
+    }
+
+    /// Distance the mouse moved since `button` was pressed, while it's held and during the frame it's released.
+    pub fn drag_delta(&self, button: MouseButton) -> Option<(f32, f32)> {
+        let start: (f32, f32) = self.drag_start(button)?;
+        let position: (f32, f32) = self.mouse_position?;
+        Some((position.0 - start.0, position.1 - start.1))
+    }
+
+    /// Whether mouse positions are pixel precise, see [`Engine::pixel_mouse`].
+    pub fn pixel_mouse(&self) -> bool {
+        self.pixel_mouse
+    }
+
+    /// All input events received since the previous frame.
+    pub fn events(&self) -> &[Event] {
+        &self.events
+    }
+
+    /// Whether the terminal supports the kitty keyboard protocol and reports real key releases.
+    pub fn keyboard_enhanced(&self) -> bool {
+        self.keyboard_enhanced
+    }
+}
+
+/// Retrieves the [`InputState`] of the current frame.
+pub fn input_state(engine: &Engine) -> &InputState {
+    &engine.input
+}
+
+/// Updates the key state from the input `events` of a frame lasting `delta_time` seconds.
+///
+/// `cell_pixel_size` is the size of a cell queried after the resize events of the frame, if any.
+pub(crate) fn update_input_state(
+    input: &mut InputState,
+    delta_time: f32,
+    events: Vec<Event>,
+    cell_pixel_size: Option<(f32, f32)>,
+) {
+    input.events.clear();
+    input.just_pressed.clear();
+    input.just_released.clear();
+    input.buttons_just_pressed.clear();
+    input.buttons_just_released.clear();
+    input.scrolled.clear();
+    input.scroll_delta = (0, 0);
+    input.mouse_events.clear();
+
+    // Drags stay readable during the frame their button is released
+    let buttons_down: &HashSet<MouseButton> = &input.buttons_down;
+    input
+        .drag_starts
+        .retain(|button, _| buttons_down.contains(button));
+
+    if !input.tracking {
+        return;
+    }
+
+    input.clock_sec += delta_time as f64;
+    let now: f64 = input.clock_sec;
+    let mut events: Vec<Event> = events;
+
+    for event in &mut events {
+        let (code, kind) = match event {
+            Event::Key(KeyEvent {
+                code,
+                kind,
+                modifiers,
+                ..
+            }) => {
+                input.modifiers = *modifiers;
+                (code, kind)
+            }
+            Event::Mouse(mouse_event) => {
+                update_mouse_state(input, mouse_event);
+                continue;
+            }
+            Event::Resize(..) => {
+                if input.pixel_mouse
+                    && let Some(cell_pixel_size) = cell_pixel_size
+                {
+                    input.cell_pixel_size = cell_pixel_size;
+                }
+                continue;
+            }
+            _ => continue,
+        };
+        let key: KeyCode = normalize_key(*code);
+
+        match kind {
+            KeyEventKind::Press | KeyEventKind::Repeat => {
+                if input.keys_down.insert(key, now).is_none() {
+                    input.just_pressed.insert(key);
+                }
+            }
+            KeyEventKind::Release => {
+                if input.keys_down.remove(&key).is_some() {
+                    input.just_released.insert(key);
+                }
+            }
+        }
+    }
+
+    input.events = events;
+
+    if !input.keyboard_enhanced {
+        let release_timeout_sec: f64 = input.release_timeout.as_secs_f64();
+        let just_released: &mut HashSet<KeyCode> = &mut input.just_released;
+        input.keys_down.retain(|key, last_seen| {
+            let held: bool = now - *last_seen <= release_timeout_sec;
+            if !held {
+                just_released.insert(*key);
+            }
+            held
+        });
+    }
+}
+
+/// Size of a single cell in pixels, or `None` if the terminal doesn't report its pixel size.
+pub(crate) fn query_cell_pixel_size() -> Option<(f32, f32)> {
+    let size: terminal::WindowSize = terminal::window_size().ok()?;
+    if size.width == 0 || size.height == 0 || size.columns == 0 || size.rows == 0 {
+        return None;
+    }
+    Some((
+        size.width as f32 / size.columns as f32,
+        size.height as f32 / size.rows as f32,
+    ))
+}
+
+/// Updates the mouse state from `mouse_event`.
+///
+/// In pixel mode, the event coordinates are converted back into cells in place,
+/// so that events read by the user are always in cells.
+fn update_mouse_state(input: &mut InputState, mouse_event: &mut MouseEvent) {
+    input.modifiers = mouse_event.modifiers;
+
+    let position: (f32, f32) = if input.pixel_mouse {
+        let (cell_width, cell_height) = input.cell_pixel_size;
+        let x: f32 = mouse_event.column as f32 / cell_width;
+        let y: f32 = mouse_event.row as f32 / cell_height;
+        mouse_event.column = x as u16;
+        mouse_event.row = y as u16;
+        (x, y)
+    } else {
+        (
+            mouse_event.column as f32 + 0.5,
+            mouse_event.row as f32 + 0.5,
+        )
+    };
+    input.mouse_position = Some(position);
+    input.mouse_events.push((mouse_event.kind, position));
+
+    match mouse_event.kind {
+        MouseEventKind::Down(button) if input.buttons_down.insert(button) => {
+            input.buttons_just_pressed.insert(button);
+            input.drag_starts.insert(button, position);
+        }
+        MouseEventKind::Up(button) if input.buttons_down.remove(&button) => {
+            input.buttons_just_released.insert(button);
+        }
+        MouseEventKind::ScrollUp => {
+            input.scrolled.insert(ScrollDirection::Up);
+            input.scroll_delta.1 -= 1;
+        }
+        MouseEventKind::ScrollDown => {
+            input.scrolled.insert(ScrollDirection::Down);
+            input.scroll_delta.1 += 1;
+        }
+        MouseEventKind::ScrollLeft => {
+            input.scrolled.insert(ScrollDirection::Left);
+            input.scroll_delta.0 -= 1;
+        }
+        MouseEventKind::ScrollRight => {
+            input.scrolled.insert(ScrollDirection::Right);
+            input.scroll_delta.0 += 1;
+        }
+        _ => {}
+    }
+}
+
+/// Names of special keys, also used for characters that have a meaning in the bindings config format.
+const KEY_NAMES: [(KeyCode, &str); 54] = [
+    (KeyCode::Char(' '), "space"),
+    (KeyCode::Char('+'), "plus"),
+    (KeyCode::Char(','), "comma"),
+    (KeyCode::Char('#'), "hash"),
+    (KeyCode::Enter, "enter"),
+    (KeyCode::Esc, "esc"),
+    (KeyCode::Tab, "tab"),
+    (KeyCode::BackTab, "backtab"),
+    (KeyCode::Backspace, "backspace"),
+    (KeyCode::Delete, "delete"),
+    (KeyCode::Insert, "insert"),
+    (KeyCode::Home, "home"),
+    (KeyCode::End, "end"),
+    (KeyCode::PageUp, "pageup"),
+    (KeyCode::PageDown, "pagedown"),
+    (KeyCode::Up, "up"),
+    (KeyCode::Down, "down"),
+    (KeyCode::Left, "left"),
+    (KeyCode::Right, "right"),
+    (KeyCode::Null, "null"),
+    (KeyCode::CapsLock, "capslock"),
+    (KeyCode::ScrollLock, "scrolllock"),
+    (KeyCode::NumLock, "numlock"),
+    (KeyCode::PrintScreen, "printscreen"),
+    (KeyCode::Pause, "pause"),
+    (KeyCode::Menu, "menu"),
+    (KeyCode::KeypadBegin, "keypadbegin"),
+    (KeyCode::Media(MediaKeyCode::Play), "media_play"),
+    (KeyCode::Media(MediaKeyCode::Pause), "media_pause"),
+    (KeyCode::Media(MediaKeyCode::PlayPause), "media_play_pause"),
+    (KeyCode::Media(MediaKeyCode::Reverse), "media_reverse"),
+    (KeyCode::Media(MediaKeyCode::Stop), "media_stop"),
+    (
+        KeyCode::Media(MediaKeyCode::FastForward),
+        "media_fast_forward",
+    ),
+    (KeyCode::Media(MediaKeyCode::Rewind), "media_rewind"),
+    (KeyCode::Media(MediaKeyCode::TrackNext), "media_track_next"),
+    (
+        KeyCode::Media(MediaKeyCode::TrackPrevious),
+        "media_track_previous",
+    ),
+    (KeyCode::Media(MediaKeyCode::Record), "media_record"),
+    (KeyCode::Media(MediaKeyCode::LowerVolume), "volume_down"),
+    (KeyCode::Media(MediaKeyCode::RaiseVolume), "volume_up"),
+    (KeyCode::Media(MediaKeyCode::MuteVolume), "volume_mute"),
+    (KeyCode::Modifier(ModifierKeyCode::LeftShift), "left_shift"),
+    (KeyCode::Modifier(ModifierKeyCode::LeftControl), "left_ctrl"),
+    (KeyCode::Modifier(ModifierKeyCode::LeftAlt), "left_alt"),
+    (KeyCode::Modifier(ModifierKeyCode::LeftSuper), "left_super"),
+    (KeyCode::Modifier(ModifierKeyCode::LeftHyper), "left_hyper"),
+    (KeyCode::Modifier(ModifierKeyCode::LeftMeta), "left_meta"),
+    (
+        KeyCode::Modifier(ModifierKeyCode::RightShift),
+        "right_shift",
+    ),
+    (
+        KeyCode::Modifier(ModifierKeyCode::RightControl),
+        "right_ctrl",
+    ),
+    (KeyCode::Modifier(ModifierKeyCode::RightAlt), "right_alt"),
+    (
+        KeyCode::Modifier(ModifierKeyCode::RightSuper),
+        "right_super",
+    ),
+    (
+        KeyCode::Modifier(ModifierKeyCode::RightHyper),
+        "right_hyper",
+    ),
+    (KeyCode::Modifier(ModifierKeyCode::RightMeta), "right_meta"),
+    (
+        KeyCode::Modifier(ModifierKeyCode::IsoLevel3Shift),
+        "iso_level3_shift",
+    ),
+    (
+        KeyCode::Modifier(ModifierKeyCode::IsoLevel5Shift),
+        "iso_level5_shift",
+    ),
+];
+
+/// Parses a key written by [`key_name`], case-insensitively for names.
+pub(crate) fn parse_key(token: &str) -> Option<KeyCode> {
+    let lower: String = token.to_ascii_lowercase();
+    if let Some((key, _)) = KEY_NAMES.iter().find(|(_, name)| *name == lower) {
+        return Some(*key);
+    }
+    if let Some(number) = lower.strip_prefix('f').and_then(|n| n.parse::<u8>().ok()) {
+        return Some(KeyCode::F(number));
+    }
+    // Characters that would get trimmed away, written by their code point, eg. `char(9)`
+    if let Some(code_point) = lower
+        .strip_prefix("char(")
+        .and_then(|rest| rest.strip_suffix(')'))
+    {
+        return code_point
+            .parse::<u32>()
+            .ok()
+            .and_then(char::from_u32)
+            .map(KeyCode::Char);
+    }
+
+    let mut chars = token.chars();
+    match (chars.next(), chars.next()) {
+        (Some(ch), None) => Some(KeyCode::Char(ch)),
+        _ => None,
+    }
+}
+
+/// The name of `key`, or `None` for keys without a name, such as ones added to crossterm after `KEY_NAMES`.
+pub(crate) fn key_name(key: KeyCode) -> Option<String> {
+    if let Some((_, name)) = KEY_NAMES.iter().find(|(k, _)| *k == key) {
+        return Some(name.to_string());
+    }
+    match key {
+        KeyCode::F(number) => Some(format!("f{number}")),
+        KeyCode::Char(ch) if ch.is_whitespace() || ch.is_control() => {
+            Some(format!("char({})", ch as u32))
+        }
+        KeyCode::Char(ch) => Some(ch.to_string()),
+        _ => None,
+    }
+}
+
+#[inline]
+pub(crate) fn normalize_key(key: KeyCode) -> KeyCode {
+    match key {
+        KeyCode::Char(ch) => KeyCode::Char(ch.to_ascii_lowercase()),
+        _ => key,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(ch: char, kind: KeyEventKind) -> Event {
+        Event::Key(KeyEvent::new_with_kind(
+            KeyCode::Char(ch),
+            KeyModifiers::NONE,
+            kind,
+        ))
+    }
+
+    fn tracking_input(keyboard_enhanced: bool) -> InputState {
+        let mut input = InputState::new();
+        input.tracking = true;
+        input.keyboard_enhanced = keyboard_enhanced;
+        input
+    }
+
+    #[test]
+    fn every_key_name_round_trips() {
+        let mut keys: Vec<KeyCode> = KEY_NAMES.iter().map(|(key, _)| *key).collect();
+        keys.extend([0, 1, 12, 255].map(KeyCode::F));
+        keys.extend(
+            [
+                'a', 'z', '0', '=', '-', 'é', '\t', '\n', '\u{a0}', '\0', '\u{7f}',
+            ]
+            .map(KeyCode::Char),
+        );
+
+        for key in keys {
+            let name: String = key_name(key).expect("key has no name");
+            assert_eq!(parse_key(&name), Some(key), "`{name}` doesn't parse back");
+        }
+    }
+
+    #[test]
+    fn pressed_keys_are_held_across_frames() {
+        let mut input = tracking_input(true);
+        update_input_state(&mut input, 0.1, vec![key('w', KeyEventKind::Press)], None);
+        assert!(input.is_down(KeyCode::Char('w')));
+        assert!(input.just_pressed(KeyCode::Char('W')));
+
+        update_input_state(&mut input, 0.1, vec![key('w', KeyEventKind::Repeat)], None);
+        assert!(input.is_down(KeyCode::Char('w')));
+        assert!(!input.just_pressed(KeyCode::Char('w')));
+
+        update_input_state(&mut input, 0.1, vec![key('w', KeyEventKind::Release)], None);
+        assert!(!input.is_down(KeyCode::Char('w')));
+        assert!(input.just_released(KeyCode::Char('w')));
+
+        update_input_state(&mut input, 0.1, vec![], None);
+        assert!(!input.just_released(KeyCode::Char('w')));
+    }
+
+    #[test]
+    fn enhanced_keyboards_hold_keys_until_released() {
+        let mut input = tracking_input(true);
+        update_input_state(&mut input, 0.1, vec![key('w', KeyEventKind::Press)], None);
+        update_input_state(&mut input, 10.0, vec![], None);
+        assert!(input.is_down(KeyCode::Char('w')));
+    }
+
+    #[test]
+    fn emulated_releases_wait_for_the_timeout() {
+        let mut input = tracking_input(false);
+        update_input_state(&mut input, 0.1, vec![key('w', KeyEventKind::Press)], None);
+
+        // Repeats keep the key held past the timeout
+        for _ in 0..10 {
+            update_input_state(&mut input, 0.2, vec![key('w', KeyEventKind::Press)], None);
+            assert!(input.is_down(KeyCode::Char('w')));
+            assert!(!input.just_pressed(KeyCode::Char('w')));
+        }
+
+        update_input_state(&mut input, 0.3, vec![], None);
+        assert!(input.is_down(KeyCode::Char('w')));
+        update_input_state(&mut input, 0.3, vec![], None);
+        assert!(!input.is_down(KeyCode::Char('w')));
+        assert!(input.just_released(KeyCode::Char('w')));
+    }
+
+    #[test]
+    fn emulated_keys_can_be_held_together() {
+        let mut input = tracking_input(false);
+        update_input_state(&mut input, 0.1, vec![key('w', KeyEventKind::Press)], None);
+        update_input_state(&mut input, 0.1, vec![key('d', KeyEventKind::Press)], None);
+        assert!(input.is_down(KeyCode::Char('w')));
+        assert!(input.is_down(KeyCode::Char('d')));
+        assert!(!input.just_released(KeyCode::Char('w')));
+
+        // Only the most recent key keeps repeating
+        update_input_state(&mut input, 0.45, vec![key('d', KeyEventKind::Press)], None);
+        assert!(!input.is_down(KeyCode::Char('w')));
+        assert!(input.just_released(KeyCode::Char('w')));
+        assert!(input.is_down(KeyCode::Char('d')));
+    }
+
+    #[test]
+    fn untracked_input_ignores_events() {
+        let mut input = InputState::new();
+        update_input_state(&mut input, 0.1, vec![key('w', KeyEventKind::Press)], None);
+        assert!(!input.is_down(KeyCode::Char('w')));
+        assert!(input.events().is_empty());
+    }
+}
@@ -0,0 +1,753 @@
+//! Named actions bound to keys, mouse buttons and scrolling.
+//!
+//! An [`ActionMap`] maps action names such as `"move_up"` or `"pause"` to any number of [`Binding`]s.
+//! Once set on the engine using [`set_action_map`], actions are updated during
+//! [`start_frame`](crate::engine::start_frame) and can be queried using [`is_action_down`],
+//! [`is_action_just_pressed`] and [`is_action_just_released`].
+//!
+//! Actions are evaluated from the [`InputState`], so input tracking is enabled automatically
+//! when an action map is set. Enable it with [`Engine::track_input`] before calling
+//! [`init`](crate::engine::init) to get real key releases on terminals supporting them.
+//!
+//! ## Config files
+//! Bindings can be loaded from and saved to a simple text format using [`load_bindings`]
+//! and [`save_bindings`]. Every line binds an action to a comma separated list of bindings:
+//!
+//! ```text
+//! # Movement
+//! move_up = w, up
+//! move_down = s, down
+//! save = ctrl+s
+//! dash = a+d
+//! fire = mouse_left
+//! zoom_in = scroll_up
+//! ```
+//!
+//! Action names can't be empty, contain `=`, `,`, `#` or line breaks, or start or end with whitespace.
+//!
+//! A binding joins modifiers (`ctrl`, `alt`, `shift`, `super`) and keys with `+`.
+//! Keys are written as single characters or by name, eg. `space`, `plus`, `enter`, `pageup`, `f5`,
+//! `media_play` or `left_shift`. Other whitespace and control characters are written by their code point, eg. `char(160)`.
+//! Multiple keys form a chord, which requires all of them to be held at once.
+//! Chords need a terminal supporting the kitty keyboard protocol, see [`InputState`] for details.
+
+use std::{collections::HashSet, fmt, fs, io, path::Path, str::FromStr};
+
+use crossterm::event::{KeyCode, KeyModifiers, MouseButton};
+
+use crate::{
+    engine::Engine,
+    input::{InputState, ScrollDirection, key_name, normalize_key, parse_key},
+};
+
+/// A single way of triggering an action.
+///
+/// `modifiers` must be held for the binding to trigger. `CONTROL`, `ALT` and `SUPER`
+/// that aren't part of `modifiers` must not be held, while `SHIFT` is ignored unless required.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Binding {
+    Key {
+        key: KeyCode,
+        modifiers: KeyModifiers,
+    },
+    /// Requires all `keys` to be held at once.
+    Chord {
+        keys: Vec<KeyCode>,
+        modifiers: KeyModifiers,
+    },
+    Mouse {
+        button: MouseButton,
+        modifiers: KeyModifiers,
+    },
+    /// Triggers for a single frame when the mouse wheel is scrolled.
+    Scroll {
+        direction: ScrollDirection,
+        modifiers: KeyModifiers,
+    },
+}
+
+impl Binding {
+    /// A binding of a single key without modifiers.
+    pub fn key(key: KeyCode) -> Self {
+        Binding::Key {
+            key,
+            modifiers: KeyModifiers::NONE,
+        }
+    }
+}
+
+/// Named actions along with their bindings.
+///
+/// # Example
+/// ```rust,no_run
+/// # use germterm::{crossterm::event::KeyCode, input::action::{ActionMap, Binding}};
+/// let mut actions = ActionMap::new();
+/// actions.bind("move_up", Binding::key(KeyCode::Char('w'))).unwrap();
+/// actions.bind("move_up", Binding::key(KeyCode::Up)).unwrap();
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct ActionMap {
+    // Kept in insertion order, so saved bindings keep the order they were written in
+    actions: Vec<(String, Vec<Binding>)>,
+}
+
+impl ActionMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a binding to `action`, keeping its existing bindings.
+    ///
+    /// Returns an error if `action` isn't a valid action name, see the [module docs](self),
+    /// since it couldn't be saved to a config file and loaded back.
+    pub fn bind(&mut self, action: &str, binding: Binding) -> Result<(), BindingParseError> {
+        if !is_valid_action_name(action) {
+            return Err(BindingParseError {
+                line: 0,
+                message: format!("invalid action name `{action}`"),
+            });
+        }
+        match self.actions.iter_mut().find(|(name, _)| name == action) {
+            Some((_, bindings)) => {
+                if !bindings.contains(&binding) {
+                    bindings.push(binding);
+                }
+            }
+            None => self.actions.push((action.to_string(), vec![binding])),
+        }
+        Ok(())
+    }
+
+    /// Removes all bindings of `action`.
+    pub fn unbind(&mut self, action: &str) {
+        self.actions.retain(|(name, _)| name != action);
+    }
+
+    /// The bindings of `action`, empty if it has none.
+    pub fn bindings(&self, action: &str) -> &[Binding] {
+        self.actions
+            .iter()
+            .find(|(name, _)| name == action)
+            .map(|(_, bindings)| bindings.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// Names of all actions with at least one binding.
+    pub fn actions(&self) -> impl Iterator<Item = &str> {
+        self.actions.iter().map(|(name, _)| name.as_str())
+    }
+}
+
+fn is_valid_action_name(name: &str) -> bool {
+    !name.is_empty() && name.trim() == name && !name.contains(['=', ',', '#', '\n', '\r'])
+}
+
+/// An [`ActionMap`] along with the per-frame state of its actions.
+#[derive(Default)]
+pub(crate) struct ActionState {
+    pub(crate) map: ActionMap,
+    down: HashSet<String>,
+    just_pressed: HashSet<String>,
+    just_released: HashSet<String>,
+}
+
+/// Sets the action map, replacing the previous one, and enables input tracking.
+pub fn set_action_map(engine: &mut Engine, map: ActionMap) {
+    engine.input.tracking = true;
+    engine.actions = ActionState {
+        map,
+        ..Default::default()
+    };
+}
+
+pub fn action_map(engine: &Engine) -> &ActionMap {
+    &engine.actions.map
+}
+
+/// Allows rebinding actions at runtime.
+pub fn action_map_mut(engine: &mut Engine) -> &mut ActionMap {
+    &mut engine.actions.map
+}
+
+/// Whether any binding of `action` is currently held.
+pub fn is_action_down(engine: &Engine, action: &str) -> bool {
+    engine.actions.down.contains(action)
+}
+
+/// Whether `action` started being triggered since the previous frame.
+pub fn is_action_just_pressed(engine: &Engine, action: &str) -> bool {
+    engine.actions.just_pressed.contains(action)
+}
+
+/// Whether `action` stopped being triggered since the previous frame.
+pub fn is_action_just_released(engine: &Engine, action: &str) -> bool {
+    engine.actions.just_released.contains(action)
+}
+
+pub(crate) fn update_action_state(actions: &mut ActionState, input: &InputState) {
+    actions.just_pressed.clear();
+    actions.just_released.clear();
+
+    for (name, bindings) in &actions.map.actions {
+        let was_down: bool = actions.down.contains(name);
+        let down: bool = bindings.iter().any(|b| is_binding_down(b, input));
+        let pressed: bool = bindings.iter().any(|b| is_binding_just_pressed(b, input));
+
+        if !was_down && (down || pressed) {
+            actions.just_pressed.insert(name.clone());
+        }
+        if was_down && !down {
+            actions.just_released.insert(name.clone());
+        }
+        if down {
+            actions.down.insert(name.clone());
+        } else {
+            actions.down.remove(name);
+        }
+    }
+
+    // Actions removed from the map while held
+    let map: &ActionMap = &actions.map;
+    actions
+        .down
+        .retain(|name| map.actions.iter().any(|(action, _)| action == name));
+}
+
+fn modifiers_match(required: KeyModifiers, held: KeyModifiers) -> bool {
+    let strict: KeyModifiers = KeyModifiers::CONTROL | KeyModifiers::ALT | KeyModifiers::SUPER;
+    held.contains(required) && (held & strict) == (required & strict)
+}
+
+fn is_binding_down(binding: &Binding, input: &InputState) -> bool {
+    match binding {
+        Binding::Key { key, modifiers } => {
+            input.is_down(*key) && modifiers_match(*modifiers, input.modifiers())
+        }
+        Binding::Chord { keys, modifiers } => {
+            !keys.is_empty()
+                && keys.iter().all(|key| input.is_down(*key))
+                && modifiers_match(*modifiers, input.modifiers())
+        }
+        Binding::Mouse { button, modifiers } => {
+            input.is_button_down(*button) && modifiers_match(*modifiers, input.modifiers())
+        }
+        Binding::Scroll {
+            direction,
+            modifiers,
+        } => input.scrolled(*direction) && modifiers_match(*modifiers, input.modifiers()),
+    }
+}
+
+fn is_binding_just_pressed(binding: &Binding, input: &InputState) -> bool {
+    match binding {
+        Binding::Key { key, modifiers } => {
+            input.just_pressed(*key) && modifiers_match(*modifiers, input.modifiers())
+        }
+        Binding::Chord { keys, modifiers } => {
+            // The chord completes with whichever key was pressed last
+            !keys.is_empty()
+                && keys
+                    .iter()
+                    .all(|key| input.is_down(*key) || input.just_pressed(*key))
+                && keys.iter().any(|key| input.just_pressed(*key))
+                && modifiers_match(*modifiers, input.modifiers())
+        }
+        Binding::Mouse { button, modifiers } => {
+            input.button_just_pressed(*button) && modifiers_match(*modifiers, input.modifiers())
+        }
+        Binding::Scroll { .. } => is_binding_down(binding, input),
+    }
+}
+
+/// An error produced when parsing bindings or binding an invalid action name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BindingParseError {
+    /// 1-based line number, or `0` when not parsing a config file.
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for BindingParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.line == 0 {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "line {}: {}", self.line, self.message)
+        }
+    }
+}
+
+impl std::error::Error for BindingParseError {}
+
+impl FromStr for Binding {
+    type Err = BindingParseError;
+
+    /// Parses a single binding, eg. `ctrl+s`, `a+d`, `mouse_left` or `scroll_up`.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let error = |message: String| BindingParseError { line: 0, message };
+
+        let mut modifiers: KeyModifiers = KeyModifiers::NONE;
+        let mut keys: Vec<KeyCode> = Vec::new();
+        let mut pointer: Option<Binding> = None;
+
+        for token in value.split('+').map(str::trim) {
+            let lower: String = token.to_ascii_lowercase();
+            match lower.as_str() {
+                "" => return Err(error(format!("empty key in binding `{value}`"))),
+                "ctrl" | "control" => modifiers |= KeyModifiers::CONTROL,
+                "alt" => modifiers |= KeyModifiers::ALT,
+                "shift" => modifiers |= KeyModifiers::SHIFT,
+                "super" => modifiers |= KeyModifiers::SUPER,
+                _ => {
+                    if let Some(binding) = parse_pointer(&lower) {
+                        if pointer.is_some() || !keys.is_empty() {
+                            return Err(error(format!(
+                                "mouse bindings can't be combined with other keys in `{value}`"
+                            )));
+                        }
+                        pointer = Some(binding);
+                    } else if pointer.is_some() {
+                        return Err(error(format!(
+                            "mouse bindings can't be combined with other keys in `{value}`"
+                        )));
+                    } else {
+                        let key: KeyCode = parse_key(token)
+                            .ok_or_else(|| error(format!("unknown key `{token}`")))?;
+                        keys.push(normalize_key(key));
+                    }
+                }
+            }
+        }
+
+        let binding: Binding = match (pointer, keys.len()) {
+            (Some(Binding::Mouse { button, .. }), _) => Binding::Mouse { button, modifiers },
+            (Some(Binding::Scroll { direction, .. }), _) => Binding::Scroll {
+                direction,
+                modifiers,
+            },
+            (_, 0) => return Err(error(format!("binding `{value}` has no key"))),
+            (_, 1) => Binding::Key {
+                key: keys[0],
+                modifiers,
+            },
+            _ => Binding::Chord { keys, modifiers },
+        };
+        Ok(binding)
+    }
+}
+
+impl fmt::Display for Binding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let modifiers: KeyModifiers = match self {
+            Binding::Key { modifiers, .. }
+            | Binding::Chord { modifiers, .. }
+            | Binding::Mouse { modifiers, .. }
+            | Binding::Scroll { modifiers, .. } => *modifiers,
+        };
+        for (modifier, name) in [
+            (KeyModifiers::CONTROL, "ctrl"),
+            (KeyModifiers::ALT, "alt"),
+            (KeyModifiers::SHIFT, "shift"),
+            (KeyModifiers::SUPER, "super"),
+        ] {
+            if modifiers.contains(modifier) {
+                write!(f, "{name}+")?;
+            }
+        }
+
+        // Keys without a name are written in their debug form, which fails to load with an unknown key error
+        let name = |key: KeyCode| key_name(key).unwrap_or_else(|| format!("{key:?}"));
+        match self {
+            Binding::Key { key, .. } => write!(f, "{}", name(*key)),
+            Binding::Chord { keys, .. } => {
+                let names: Vec<String> = keys.iter().map(|key| name(*key)).collect();
+                write!(f, "{}", names.join("+"))
+            }
+            Binding::Mouse { button, .. } => write!(
+                f,
+                "{}",
+                match button {
+                    MouseButton::Left => "mouse_left",
+                    MouseButton::Right => "mouse_right",
+                    MouseButton::Middle => "mouse_middle",
+                }
+            ),
+            Binding::Scroll { direction, .. } => write!(
+                f,
+                "{}",
+                match direction {
+                    ScrollDirection::Up => "scroll_up",
+                    ScrollDirection::Down => "scroll_down",
+                    ScrollDirection::Left => "scroll_left",
+                    ScrollDirection::Right => "scroll_right",
+                }
+            ),
+        }
+    }
+}
+
+fn parse_pointer(token: &str) -> Option<Binding> {
+    let modifiers: KeyModifiers = KeyModifiers::NONE;
+    let binding: Binding = match token {
+        "mouse_left" => Binding::Mouse {
+            button: MouseButton::Left,
+            modifiers,
+        },
+        "mouse_right" => Binding::Mouse {
+            button: MouseButton::Right,
+            modifiers,
+        },
+        "mouse_middle" => Binding::Mouse {
+            button: MouseButton::Middle,
+            modifiers,
+        },
+        "scroll_up" => Binding::Scroll {
+            direction: ScrollDirection::Up,
+            modifiers,
+        },
+        "scroll_down" => Binding::Scroll {
+            direction: ScrollDirection::Down,
+            modifiers,
+        },
+        "scroll_left" => Binding::Scroll {
+            direction: ScrollDirection::Left,
+            modifiers,
+        },
+        "scroll_right" => Binding::Scroll {
+            direction: ScrollDirection::Right,
+            modifiers,
+        },
+        _ => return None,
+    };
+    Some(binding)
+}
+
+/// Whether `binding` can be written to a config file and loaded back.
+fn is_binding_nameable(binding: &Binding) -> bool {
+    match binding {
+        Binding::Key { key, .. } => key_name(*key).is_some(),
+        Binding::Chord { keys, .. } => keys.iter().all(|key| key_name(*key).is_some()),
+        Binding::Mouse { .. } | Binding::Scroll { .. } => true,
+    }
+}
+
+/// Parses an [`ActionMap`] from the config format described in the [module docs](self).
+///
+/// # Example
+/// ```rust
+/// # use germterm::{crossterm::event::KeyCode, input::action::{parse_bindings, Binding}};
+/// let actions = parse_bindings("move_up = w, up").unwrap();
+/// assert_eq!(actions.bindings("move_up")[1], Binding::key(KeyCode::Up));
+/// ```
+pub fn parse_bindings(source: &str) -> Result<ActionMap, BindingParseError> {
+    let mut map: ActionMap = ActionMap::new();
+
+    for (index, line) in source.lines().enumerate() {
+        let line: &str = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let error = |message: String| BindingParseError {
+            line: index + 1,
+            message,
+        };
+        let (action, bindings) = line
+            .split_once('=')
+            .ok_or_else(|| error("expected `action = bindings`".to_string()))?;
+        let action: &str = action.trim();
+        if action.is_empty() {
+            return Err(error("missing action name".to_string()));
+        }
+
+        for binding in bindings.split(',') {
+            let binding: Binding =
+                binding
+                    .trim()
+                    .parse()
+                    .map_err(|err: BindingParseError| BindingParseError {
+                        line: index + 1,
+                        message: err.message,
+                    })?;
+            map.bind(action, binding)
+                .map_err(|err| error(err.message))?;
+        }
+    }
+
+    Ok(map)
+}
+
+/// Formats an [`ActionMap`] into the config format described in the [module docs](self).
+///
+/// Bindings of keys without a name, which newer versions of crossterm might add, are left out.
+pub fn format_bindings(map: &ActionMap) -> String {
+    let mut output: String = String::new();
+    for (action, bindings) in &map.actions {
+        let bindings: Vec<String> = bindings
+            .iter()
+            .filter(|binding| is_binding_nameable(binding))
+            .map(Binding::to_string)
+            .collect();
+        if bindings.is_empty() {
+            continue;
+        }
+        output.push_str(&format!("{action} = {}\n", bindings.join(", ")));
+    }
+    output
+}
+
+/// Loads an [`ActionMap`] from a config file.
+///
+/// Parse errors are returned as [`io::ErrorKind::InvalidData`].
+pub fn load_bindings(path: impl AsRef<Path>) -> io::Result<ActionMap> {
+    let source: String = fs::read_to_string(path)?;
+    parse_bindings(&source).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+/// Saves an [`ActionMap`] to a config file, replacing its contents.
+pub fn save_bindings(map: &ActionMap, path: impl AsRef<Path>) -> io::Result<()> {
+    fs::write(path, format_bindings(map))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::{
+        Event, KeyEvent, KeyEventKind, MediaKeyCode, ModifierKeyCode, MouseEvent, MouseEventKind,
+    };
+
+    use crate::input::update_input_state;
+
+    fn key(code: KeyCode, modifiers: KeyModifiers, kind: KeyEventKind) -> Event {
+        Event::Key(KeyEvent::new_with_kind(code, modifiers, kind))
+    }
+
+    fn press(ch: char) -> Event {
+        key(KeyCode::Char(ch), KeyModifiers::NONE, KeyEventKind::Press)
+    }
+
+    fn release(ch: char) -> Event {
+        key(KeyCode::Char(ch), KeyModifiers::NONE, KeyEventKind::Release)
+    }
+
+    /// Runs a frame with `events`, returning whether `action` is down, just pressed and just released.
+    fn frame(
+        actions: &mut ActionState,
+        input: &mut InputState,
+        events: Vec<Event>,
+        action: &str,
+    ) -> (bool, bool, bool) {
+        update_input_state(input, 0.016, events, None);
+        update_action_state(actions, input);
+        (
+            actions.down.contains(action),
+            actions.just_pressed.contains(action),
+            actions.just_released.contains(action),
+        )
+    }
+
+    fn action_test_state(source: &str) -> (ActionState, InputState) {
+        let mut input = InputState::new();
+        input.tracking = true;
+        input.keyboard_enhanced = true;
+        let actions = ActionState {
+            map: parse_bindings(source).unwrap(),
+            ..Default::default()
+        };
+        (actions, input)
+    }
+
+    #[test]
+    fn actions_follow_any_of_their_bindings() {
+        let (mut actions, mut input) = action_test_state("move_up = w, up");
+        let up = |kind| key(KeyCode::Up, KeyModifiers::NONE, kind);
+
+        assert_eq!(
+            frame(&mut actions, &mut input, vec![press('w')], "move_up"),
+            (true, true, false)
+        );
+        assert_eq!(
+            frame(
+                &mut actions,
+                &mut input,
+                vec![up(KeyEventKind::Press), release('w')],
+                "move_up"
+            ),
+            (true, false, false)
+        );
+        assert_eq!(
+            frame(&mut actions, &mut input, vec![], "move_up"),
+            (true, false, false)
+        );
+        assert_eq!(
+            frame(
+                &mut actions,
+                &mut input,
+                vec![up(KeyEventKind::Release)],
+                "move_up"
+            ),
+            (false, false, true)
+        );
+        assert_eq!(
+            frame(&mut actions, &mut input, vec![], "move_up"),
+            (false, false, false)
+        );
+    }
+
+    #[test]
+    fn actions_require_exactly_their_modifiers() {
+        let (mut actions, mut input) = action_test_state("save = ctrl+s");
+        let s = |modifiers| key(KeyCode::Char('s'), modifiers, KeyEventKind::Press);
+
+        assert!(
+            !frame(
+                &mut actions,
+                &mut input,
+                vec![s(KeyModifiers::NONE)],
+                "save"
+            )
+            .0
+        );
+        assert!(
+            !frame(
+                &mut actions,
+                &mut input,
+                vec![s(KeyModifiers::CONTROL | KeyModifiers::ALT)],
+                "save"
+            )
+            .0
+        );
+        assert_eq!(
+            frame(
+                &mut actions,
+                &mut input,
+                vec![s(KeyModifiers::CONTROL | KeyModifiers::SHIFT)],
+                "save"
+            ),
+            (true, true, false)
+        );
+    }
+
+    #[test]
+    fn chords_trigger_once_all_keys_are_held() {
+        let (mut actions, mut input) = action_test_state("dash = a+d");
+
+        assert!(!frame(&mut actions, &mut input, vec![press('a')], "dash").0);
+        assert_eq!(
+            frame(&mut actions, &mut input, vec![press('d')], "dash"),
+            (true, true, false)
+        );
+        assert_eq!(
+            frame(&mut actions, &mut input, vec![release('a')], "dash"),
+            (false, false, true)
+        );
+    }
+
+    #[test]
+    fn mouse_and_scroll_bindings_trigger_actions() {
+        let (mut actions, mut input) = action_test_state("fire = mouse_left\nzoom = scroll_up");
+        let mouse = |kind| {
+            Event::Mouse(MouseEvent {
+                kind,
+                column: 0,
+                row: 0,
+                modifiers: KeyModifiers::NONE,
+            })
+        };
+
+        let down = mouse(MouseEventKind::Down(MouseButton::Left));
+        assert_eq!(
+            frame(&mut actions, &mut input, vec![down], "fire"),
+            (true, true, false)
+        );
+        let up = mouse(MouseEventKind::Up(MouseButton::Left));
+        assert_eq!(
+            frame(&mut actions, &mut input, vec![up], "fire"),
+            (false, false, true)
+        );
+
+        let scroll = mouse(MouseEventKind::ScrollUp);
+        assert_eq!(
+            frame(&mut actions, &mut input, vec![scroll], "zoom"),
+            (true, true, false)
+        );
+        assert_eq!(
+            frame(&mut actions, &mut input, vec![], "zoom"),
+            (false, false, true)
+        );
+    }
+
+    #[test]
+    fn formatted_bindings_parse_back() {
+        let mut map: ActionMap = ActionMap::new();
+        map.bind("jump", Binding::key(KeyCode::Char(' '))).unwrap();
+        map.bind("jump", Binding::key(KeyCode::Up)).unwrap();
+        map.bind(
+            "save",
+            Binding::Key {
+                key: KeyCode::Char('s'),
+                modifiers: KeyModifiers::CONTROL | KeyModifiers::SHIFT,
+            },
+        )
+        .unwrap();
+        map.bind(
+            "dash",
+            Binding::Chord {
+                keys: vec![KeyCode::Char('+'), KeyCode::Char(',')],
+                modifiers: KeyModifiers::NONE,
+            },
+        )
+        .unwrap();
+        map.bind(
+            "media",
+            Binding::key(KeyCode::Media(MediaKeyCode::PlayPause)),
+        )
+        .unwrap();
+        map.bind(
+            "sprint",
+            Binding::key(KeyCode::Modifier(ModifierKeyCode::LeftShift)),
+        )
+        .unwrap();
+        map.bind("odd", Binding::key(KeyCode::Char('\u{a0}')))
+            .unwrap();
+        map.bind("odd", Binding::key(KeyCode::Null)).unwrap();
+        map.bind(
+            "fire",
+            Binding::Mouse {
+                button: MouseButton::Left,
+                modifiers: KeyModifiers::ALT,
+            },
+        )
+        .unwrap();
+        map.bind(
+            "zoom",
+            Binding::Scroll {
+                direction: ScrollDirection::Up,
+                modifiers: KeyModifiers::NONE,
+            },
+        )
+        .unwrap();
+
+        let parsed: ActionMap = parse_bindings(&format_bindings(&map)).unwrap();
+        for action in [
+            "jump", "save", "dash", "media", "sprint", "odd", "fire", "zoom",
+        ] {
+            assert_eq!(parsed.bindings(action), map.bindings(action), "`{action}`");
+        }
+    }
+
+    #[test]
+    fn action_names_that_dont_load_back_are_rejected() {
+        let mut map: ActionMap = ActionMap::new();
+        for name in ["", "a=b", "a,b", "#a", "a#b", " a", "a ", "a\nb"] {
+            assert!(
+                map.bind(name, Binding::key(KeyCode::Up)).is_err(),
+                "`{name}`"
+            );
+        }
+        assert!(map.bind("move up", Binding::key(KeyCode::Up)).is_ok());
+        assert_eq!(map.actions().collect::<Vec<_>>(), ["move up"]);
+
+        assert_eq!(parse_bindings("ok = w\na,b = w").unwrap_err().line, 2);
+    }
+}
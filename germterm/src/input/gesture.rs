@@ -0,0 +1,322 @@
+//! Recognition of mouse gestures.
+//!
+//! Terminals only report mouse buttons going down and up, and the mouse moving.
+//! While input tracking is enabled, [`start_frame`](crate::engine::start_frame) turns these into
+//! higher-level [`Gesture`]s, read using [`gestures`]:
+//!
+//! - Clicks, counting consecutive clicks for double and triple clicks.
+//! - Long presses of a button held in place.
+//! - Drags, starting once the mouse moved a threshold away from where the button was pressed.
+//! - The mouse entering and leaving [hit regions](crate::input::hit_region).
+//!
+//! Gestures are timed using [`Engine::game_time`], so they follow the [time scale](crate::engine::set_time_scale)
+//! and long presses wait while the game is paused. Menus shown during a pause can time them using
+//! [`Engine::real_time`] instead with [`GestureConfig::unscaled`].
+//! Their thresholds can be changed with [`Engine::gesture_config`].
+//!
+//! # Example
+//! ```rust,no_run
+//! # use germterm::{engine::{Engine, start_frame}, input::gesture::{Gesture, gestures}};
+//! let mut engine = Engine::new(40, 20).track_input(true);
+//!
+//! start_frame(&mut engine);
+//! for gesture in gestures(&engine) {
+//!     match gesture {
+//!         Gesture::Click { count: 2, .. } => {
+//!             // Double click
+//!         }
+//!         Gesture::DragMove { dx, dy, .. } => {
+//!             // Pan the camera
+//!         }
+//!         _ => {}
+//!     }
+//! }
+//! ```
+
+use std::collections::HashMap;
+
+use crossterm::event::{MouseButton, MouseEventKind};
+
+use crate::{engine::Engine, input::hit_region::hovered_region};
+
+/// A higher-level mouse event, see the [module docs](self).
+///
+/// Positions are in fractional cells, like [`InputState::mouse_position`](crate::input::InputState::mouse_position).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Gesture {
+    /// `button` was pressed and released without dragging or long pressing.
+    ///
+    /// `count` is `1` for a single click, `2` for a double click, `3` for a triple click and so on.
+    Click {
+        button: MouseButton,
+        x: f32,
+        y: f32,
+        count: u32,
+    },
+    /// `button` was held in place for [`GestureConfig::long_press_sec`].
+    ///
+    /// Releasing the button afterwards doesn't produce a click.
+    LongPress { button: MouseButton, x: f32, y: f32 },
+    /// The mouse moved past [`GestureConfig::drag_threshold`] while `button` was held.
+    /// `x`, `y` is the position the button was pressed at.
+    DragStart { button: MouseButton, x: f32, y: f32 },
+    /// The mouse moved during a drag, by `dx`, `dy` since the previous move.
+    DragMove {
+        button: MouseButton,
+        x: f32,
+        y: f32,
+        dx: f32,
+        dy: f32,
+    },
+    /// `button` was released, ending a drag.
+    DragEnd { button: MouseButton, x: f32, y: f32 },
+    /// The mouse entered the hit region with the ID `region`.
+    HoverEnter { region: u64 },
+    /// The mouse left the hit region with the ID `region`.
+    HoverLeave { region: u64 },
+}
+
+/// Thresholds used to recognize gestures.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GestureConfig {
+    /// Maximum seconds between releasing a button and pressing it again for clicks to count as consecutive.
+    pub multi_click_interval_sec: f32,
+    /// Maximum distance in cells between consecutive clicks.
+    pub multi_click_distance: f32,
+    /// Distance in cells the mouse has to move while a button is held to start a drag.
+    pub drag_threshold: f32,
+    /// Seconds a button has to be held in place for a long press.
+    pub long_press_sec: f32,
+    /// Whether gestures are timed using [`Engine::real_time`] rather than [`Engine::game_time`],
+    /// ignoring the time scale and pausing.
+    pub unscaled: bool,
+}
+
+impl Default for GestureConfig {
+    fn default() -> Self {
+        Self {
+            multi_click_interval_sec: 0.4,
+            multi_click_distance: 1.0,
+            drag_threshold: 1.0,
+            long_press_sec: 0.6,
+            unscaled: false,
+        }
+    }
+}
+
+struct Press {
+    start: (f32, f32),
+    last: (f32, f32),
+    time: f32,
+    dragging: bool,
+    long_pressed: bool,
+}
+
+struct LastClick {
+    button: MouseButton,
+    position: (f32, f32),
+    time: f32,
+    count: u32,
+}
+
+#[derive(Default)]
+pub(crate) struct GestureState {
+    pub(crate) config: GestureConfig,
+    gestures: Vec<Gesture>,
+    presses: HashMap<MouseButton, Press>,
+    last_click: Option<LastClick>,
+    hovered: Option<u64>,
+}
+
+/// Gestures recognized since the previous frame.
+pub fn gestures(engine: &Engine) -> &[Gesture] {
+    &engine.gestures.gestures
+}
+
+/// Recognizes gestures from the mouse events of the current frame.
+pub(crate) fn update_gestures(engine: &mut Engine) {
+    let config: GestureConfig = engine.gestures.config;
+    let now: f32 = if config.unscaled {
+        engine.real_time
+    } else {
+        engine.game_time
+    };
+    let hovered: Option<u64> = hovered_region(engine);
+    let state: &mut GestureState = &mut engine.gestures;
+    state.gestures.clear();
+
+    for &(kind, position) in &engine.input.mouse_events {
+        match kind {
+            MouseEventKind::Down(button) => {
+                state.presses.insert(
+                    button,
+                    Press {
+                        start: position,
+                        last: position,
+                        time: now,
+                        dragging: false,
+                        long_pressed: false,
+                    },
+                );
+            }
+            MouseEventKind::Drag(button) => {
+                let Some(press) = state.presses.get_mut(&button) else {
+                    continue;
+                };
+                if !press.dragging {
+                    if distance(press.start, position) < config.drag_threshold {
+                        continue;
+                    }
+                    press.dragging = true;
+                    state.gestures.push(Gesture::DragStart {
+                        button,
+                        x: press.start.0,
+                        y: press.start.1,
+                    });
+                }
+                state.gestures.push(Gesture::DragMove {
+                    button,
+                    x: position.0,
+                    y: position.1,
+                    dx: position.0 - press.last.0,
+                    dy: position.1 - press.last.1,
+                });
+                press.last = position;
+            }
+            MouseEventKind::Up(button) => {
+                let Some(press) = state.presses.remove(&button) else {
+                    continue;
+                };
+                if press.dragging {
+                    state.gestures.push(Gesture::DragEnd {
+                        button,
+                        x: position.0,
+                        y: position.1,
+                    });
+                    continue;
+                }
+                if press.long_pressed {
+                    continue;
+                }
+
+                let count: u32 = match &state.last_click {
+                    Some(last_click)
+                        if last_click.button == button
+                            && press.time - last_click.time <= config.multi_click_interval_sec
+                            && distance(last_click.position, press.start)
+                                <= config.multi_click_distance =>
+                    {
+                        last_click.count + 1
+                    }
+                    _ => 1,
+                };
+                state.last_click = Some(LastClick {
+                    button,
+                    position: press.start,
+                    time: now,
+                    count,
+                });
+                state.gestures.push(Gesture::Click {
+                    button,
+                    x: press.start.0,
+                    y: press.start.1,
+                    count,
+                });
+            }
+            _ => {}
+        }
+    }
+
+    // Iterated in a fixed order, keeping replays deterministic
+    for button in [MouseButton::Left, MouseButton::Right, MouseButton::Middle] {
+        let Some(press) = state.presses.get_mut(&button) else {
+            continue;
+        };
+        if !press.dragging && !press.long_pressed && now - press.time >= config.long_press_sec {
+            press.long_pressed = true;
+            state.gestures.push(Gesture::LongPress {
+                button,
+                x: press.start.0,
+                y: press.start.1,
+            });
+        }
+    }
+
+    if hovered != state.hovered {
+        if let Some(region) = state.hovered {
+            state.gestures.push(Gesture::HoverLeave { region });
+        }
+        if let Some(region) = hovered {
+            state.gestures.push(Gesture::HoverEnter { region });
+        }
+        state.hovered = hovered;
+    }
+}
+
+fn distance(a: (f32, f32), b: (f32, f32)) -> f32 {
+    (a.0 - b.0).hypot(a.1 - b.1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn long_presses(engine: &Engine) -> usize {
+        gestures(engine)
+            .iter()
+            .filter(|gesture| matches!(gesture, Gesture::LongPress { .. }))
+            .count()
+    }
+
+    fn hold_while_paused(unscaled: bool) -> usize {
+        let mut engine = Engine::new(40, 20).gesture_config(GestureConfig {
+            unscaled,
+            ..GestureConfig::default()
+        });
+        engine
+            .input
+            .mouse_events
+            .push((MouseEventKind::Down(MouseButton::Left), (3.0, 4.0)));
+        update_gestures(&mut engine);
+        engine.input.mouse_events.clear();
+
+        let mut total: usize = 0;
+        for _ in 0..10 {
+            // Paused: real time passes, game time doesn't
+            engine.real_time += 0.1;
+            update_gestures(&mut engine);
+            total += long_presses(&engine);
+        }
+        total
+    }
+
+    #[test]
+    fn gestures_follow_game_time_by_default() {
+        assert_eq!(hold_while_paused(false), 0);
+    }
+
+    #[test]
+    fn unscaled_gestures_follow_real_time() {
+        assert_eq!(hold_while_paused(true), 1);
+    }
+
+    #[test]
+    fn consecutive_clicks_are_counted() {
+        let mut engine = Engine::new(40, 20);
+        let mut counts: Vec<u32> = Vec::new();
+        for _ in 0..3 {
+            engine.input.mouse_events = vec![
+                (MouseEventKind::Down(MouseButton::Left), (3.0, 4.0)),
+                (MouseEventKind::Up(MouseButton::Left), (3.0, 4.0)),
+            ];
+            update_gestures(&mut engine);
+            for gesture in gestures(&engine) {
+                if let Gesture::Click { count, .. } = gesture {
+                    counts.push(*count);
+                }
+            }
+            engine.game_time += 0.1;
+        }
+        assert_eq!(counts, [1, 2, 3]);
+    }
+}
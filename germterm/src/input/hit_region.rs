@@ -0,0 +1,228 @@
+//! Hit testing of drawn elements against the mouse.
+//!
+//! Draw code registers rectangles with IDs every frame using [`register_hit_region`].
+//! Regions registered during a frame become active at the end of it, so input code in the
+//! following frame can ask which region is hovered or clicked using [`hovered_region`]
+//! and [`clicked_region`]. Clicks are reported when the button is released over the region it was pressed on.
+//!
+//! When regions overlap, the one on the highest layer wins. Within a layer, the last registered region wins,
+//! matching the drawing order.
+//!
+//! Requires input tracking to be enabled using [`Engine::track_input`].
+//!
+//! # Example
+//! ```rust,no_run
+//! # use germterm::{crossterm::event::MouseButton, draw::draw_text, engine::{Engine, start_frame, end_frame}, layer::create_layer, input::hit_region::{clicked_region, register_hit_region}};
+//! const PLAY_BUTTON: u64 = 1;
+//!
+//! let mut engine = Engine::new(40, 20).track_input(true);
+//! let layer = create_layer(&mut engine, 0);
+//!
+//! loop {
+//!     start_frame(&mut engine);
+//!
+//!     if clicked_region(&engine, MouseButton::Left) == Some(PLAY_BUTTON) {
+//!         // Start the game
+//!     }
+//!
+//!     draw_text(&mut engine, layer, 16, 9, "[ PLAY ]");
+//!     register_hit_region(&mut engine, layer, PLAY_BUTTON, 16.0, 9.0, 8.0, 1.0);
+//!
+//!     end_frame(&mut engine).unwrap();
+//! }
+//! ```
+
+use crossterm::event::{MouseButton, MouseEventKind};
+
+use crate::{engine::Engine, layer::LayerIndex};
+
+#[derive(Clone, Copy)]
+pub(crate) struct HitRegion {
+    id: u64,
+    layer: usize,
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+}
+
+#[derive(Default)]
+pub(crate) struct HitRegions {
+    /// Regions registered during the previous frame, used for queries.
+    active: Vec<HitRegion>,
+    /// Regions registered during the current frame.
+    pending: Vec<HitRegion>,
+}
+
+/// Registers a rectangle with an `id` for hit testing during the next frame.
+///
+/// Coordinates are in cells and can be fractional.
+pub fn register_hit_region(
+    engine: &mut Engine,
+    layer_index: LayerIndex,
+    id: u64,
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+) {
+    engine.hit_regions.pending.push(HitRegion {
+        id,
+        layer: layer_index.0,
+        x,
+        y,
+        width,
+        height,
+    });
+}
+
+/// Makes the regions registered during the current frame active.
+pub(crate) fn swap_hit_regions(hit_regions: &mut HitRegions) {
+    std::mem::swap(&mut hit_regions.active, &mut hit_regions.pending);
+    hit_regions.pending.clear();
+}
+
+/// ID of the topmost region at `x`, `y`.
+pub fn hit_test(engine: &Engine, x: f32, y: f32) -> Option<u64> {
+    engine
+        .hit_regions
+        .active
+        .iter()
+        .filter(|region| {
+            x >= region.x
+                && x < region.x + region.width
+                && y >= region.y
+                && y < region.y + region.height
+        })
+        // `max_by_key` returns the last maximum, which is the last registered region
+        .max_by_key(|region| region.layer)
+        .map(|region| region.id)
+}
+
+/// ID of the topmost region under the mouse.
+pub fn hovered_region(engine: &Engine) -> Option<u64> {
+    let (x, y) = engine.input.mouse_position()?;
+    hit_test(engine, x, y)
+}
+
+/// ID of the region `button` was clicked on since the previous frame.
+///
+/// A click is reported when `button` is released over the same topmost region it was pressed on,
+/// so dragging off a region before releasing cancels the click.
+pub fn clicked_region(engine: &Engine, button: MouseButton) -> Option<u64> {
+    if !engine.input.button_just_released(button) {
+        return None;
+    }
+    let (release_x, release_y) = engine
+        .input
+        .mouse_events
+        .iter()
+        .rev()
+        .find_map(|(kind, position)| (*kind == MouseEventKind::Up(button)).then_some(*position))?;
+    let (press_x, press_y) = engine.input.drag_start(button)?;
+
+    let id: u64 = hit_test(engine, press_x, press_y)?;
+    (hit_test(engine, release_x, release_y) == Some(id)).then_some(id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::update_input_state;
+    use crossterm::event::{Event, KeyModifiers, MouseEvent};
+
+    fn mouse(kind: MouseEventKind, column: u16, row: u16) -> Event {
+        Event::Mouse(MouseEvent {
+            kind,
+            column,
+            row,
+            modifiers: KeyModifiers::NONE,
+        })
+    }
+
+    /// Engine with the regions `(layer, id, x, y, width, height)` registered and made active.
+    fn hit_test_engine(regions: &[(usize, u64, f32, f32, f32, f32)]) -> Engine {
+        let mut engine = Engine::new(40, 20).track_input(true);
+        for &(layer, id, x, y, width, height) in regions {
+            register_hit_region(&mut engine, LayerIndex(layer), id, x, y, width, height);
+        }
+        swap_hit_regions(&mut engine.hit_regions);
+        engine
+    }
+
+    fn frame(engine: &mut Engine, events: Vec<Event>) {
+        update_input_state(&mut engine.input, 1.0 / 60.0, events, None);
+    }
+
+    #[test]
+    fn overlapping_regions_resolve_by_layer_then_registration_order() {
+        let engine = hit_test_engine(&[
+            (1, 1, 0.0, 0.0, 10.0, 10.0),
+            (0, 2, 5.0, 5.0, 10.0, 10.0),
+            (1, 3, 8.0, 8.0, 4.0, 4.0),
+        ]);
+
+        assert_eq!(hit_test(&engine, 2.0, 2.0), Some(1));
+        assert_eq!(hit_test(&engine, 6.0, 6.0), Some(1));
+        assert_eq!(hit_test(&engine, 12.0, 6.0), Some(2));
+        assert_eq!(hit_test(&engine, 9.0, 9.0), Some(3));
+        assert_eq!(hit_test(&engine, 20.0, 20.0), None);
+        // Right and bottom edges are exclusive
+        assert_eq!(hit_test(&engine, 15.0, 6.0), None);
+    }
+
+    #[test]
+    fn regions_become_active_after_the_swap() {
+        let mut engine = hit_test_engine(&[(0, 1, 0.0, 0.0, 5.0, 5.0)]);
+        register_hit_region(&mut engine, LayerIndex(0), 2, 0.0, 0.0, 5.0, 5.0);
+        assert_eq!(hit_test(&engine, 1.0, 1.0), Some(1));
+
+        swap_hit_regions(&mut engine.hit_regions);
+        assert_eq!(hit_test(&engine, 1.0, 1.0), Some(2));
+
+        // Regions not registered again during a frame are gone after the next swap
+        swap_hit_regions(&mut engine.hit_regions);
+        assert_eq!(hit_test(&engine, 1.0, 1.0), None);
+    }
+
+    #[test]
+    fn clicks_are_reported_on_release_over_the_pressed_region() {
+        let mut engine = hit_test_engine(&[(0, 1, 0.0, 0.0, 5.0, 5.0), (0, 2, 5.0, 0.0, 5.0, 5.0)]);
+        let left = MouseButton::Left;
+
+        frame(&mut engine, vec![mouse(MouseEventKind::Down(left), 1, 1)]);
+        assert_eq!(clicked_region(&engine, left), None);
+        frame(&mut engine, vec![mouse(MouseEventKind::Drag(left), 3, 2)]);
+        assert_eq!(clicked_region(&engine, left), None);
+        frame(&mut engine, vec![mouse(MouseEventKind::Up(left), 3, 2)]);
+        assert_eq!(clicked_region(&engine, left), Some(1));
+        assert_eq!(clicked_region(&engine, MouseButton::Right), None);
+        frame(&mut engine, vec![]);
+        assert_eq!(clicked_region(&engine, left), None);
+
+        // Pressing and releasing within a single frame
+        frame(
+            &mut engine,
+            vec![
+                mouse(MouseEventKind::Down(left), 6, 1),
+                mouse(MouseEventKind::Up(left), 6, 1),
+                mouse(MouseEventKind::Moved, 20, 10),
+            ],
+        );
+        assert_eq!(clicked_region(&engine, left), Some(2));
+    }
+
+    #[test]
+    fn releasing_outside_the_pressed_region_cancels_the_click() {
+        let mut engine = hit_test_engine(&[(0, 1, 0.0, 0.0, 5.0, 5.0), (0, 2, 5.0, 0.0, 5.0, 5.0)]);
+        let left = MouseButton::Left;
+
+        frame(&mut engine, vec![mouse(MouseEventKind::Down(left), 1, 1)]);
+        frame(&mut engine, vec![mouse(MouseEventKind::Up(left), 6, 1)]);
+        assert_eq!(clicked_region(&engine, left), None);
+
+        frame(&mut engine, vec![mouse(MouseEventKind::Down(left), 20, 10)]);
+        frame(&mut engine, vec![mouse(MouseEventKind::Up(left), 1, 1)]);
+        assert_eq!(clicked_region(&engine, left), None);
+    }
+}
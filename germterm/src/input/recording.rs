@@ -0,0 +1,764 @@
+//! Recording and deterministic replay of input.
+//!
+//! [`start_recording`] writes the input events and `delta_time` of every frame into a file.
+//! Loading that file with [`load_recording`] and passing it to [`start_replay`] feeds the recorded
+//! frames back through [`start_frame`](crate::engine::start_frame) in place of the terminal,
+//! reproducing the recorded session exactly. Together with [`Engine::headless`],
+//! recordings can be replayed without a terminal, eg. for regression tests.
+//!
+//! Both recording and replaying enable input tracking, so events should be read using
+//! [`InputState::events`](crate::input::InputState::events) instead of [`poll_input`](crate::input::poll_input).
+//!
+//! ## Determinism
+//! A recording also stores a random seed, which seeds the particle system on recording and on replay.
+//! Games using randomness of their own should seed their generators with [`recording_seed`].
+//! Time should only be taken from [`Engine::delta_time`] and [`Engine::game_time`], never from the system clock.
+//!
+//! ## File format
+//! Recordings are stored as text, one entry per line. Floats are written as the hex bits of an `f32`
+//! so that they are restored bit for bit. Keys are named like in [bindings config files](crate::input::action).
+//!
+//! ```text
+//! germterm-recording 1
+//! seed 8031954318
+//! keyboard_enhanced false
+//! frame 3c888889
+//! key w 0 press 0
+//! mouse down:left 12 4 0
+//! frame 3c888889
+//! resize 80 24
+//! cell_pixel_size 41100000 41a00000
+//! ```
+//!
+//! A `cell_pixel_size` line before the first frame is the cell size when recording started.
+//! Within a frame, it follows a resize and holds the cell size queried from the terminal after it,
+//! so that replays never query the terminal.
+
+use std::{
+    fmt::{self, Write as _},
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+use crossterm::event::{
+    Event, KeyEvent, KeyEventKind, KeyEventState, KeyModifiers, MouseButton, MouseEvent,
+    MouseEventKind,
+};
+
+use crate::{
+    engine::Engine,
+    input::{key_name, parse_key},
+    particle::seed_particle_rng,
+};
+
+const HEADER: &str = "germterm-recording 1";
+
+/// A recorded session, replayed using [`start_replay`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct InputRecording {
+    /// Seeds the particle system on replay, see [`recording_seed`].
+    pub seed: u64,
+    /// Whether the recorded terminal reported real key releases.
+    pub keyboard_enhanced: bool,
+    /// Size of a cell in pixels if pixel precise mouse positions were enabled while recording.
+    pub cell_pixel_size: Option<(f32, f32)>,
+    pub frames: Vec<RecordedFrame>,
+}
+
+/// The input of a single recorded frame.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RecordedFrame {
+    pub delta_time: f32,
+    pub events: Vec<Event>,
+    /// Size of a cell in pixels after the resize events of the frame,
+    /// if pixel precise mouse positions were enabled.
+    pub cell_pixel_size: Option<(f32, f32)>,
+}
+
+/// Where [`start_frame`](crate::engine::start_frame) takes its input from.
+#[derive(Default)]
+pub(crate) enum InputSource {
+    #[default]
+    Terminal,
+    Recording {
+        writer: BufWriter<File>,
+        seed: u64,
+        // The first write error, reported by `stop_recording`
+        error: Option<io::Error>,
+    },
+    Replay {
+        recording: InputRecording,
+        next_frame: usize,
+    },
+}
+
+/// Starts recording the input of every frame into the file at `path`, replacing its contents.
+///
+/// Reseeds the particle system with a random seed stored in the recording.
+/// Should be called after [`init`](crate::engine::init), so that the terminal capabilities are known.
+/// Every frame is flushed to the file, so the recording survives crashes.
+///
+/// # Example
+/// ```rust,no_run
+/// # use germterm::{engine::{Engine, init}, input::recording::start_recording};
+/// let mut engine = Engine::new(40, 20).track_input(true);
+/// init(&mut engine).unwrap();
+/// start_recording(&mut engine, "session.rec").unwrap();
+/// ```
+pub fn start_recording(engine: &mut Engine, path: impl AsRef<Path>) -> io::Result<()> {
+    let seed: u64 = rand::random();
+    let mut writer: BufWriter<File> = BufWriter::new(File::create(path)?);
+    let mut header: String = String::new();
+    let _ = write_header(
+        &mut header,
+        seed,
+        engine.input.keyboard_enhanced,
+        recorded_cell_pixel_size(engine),
+    );
+    writer.write_all(header.as_bytes())?;
+    writer.flush()?;
+
+    seed_particle_rng(engine, seed);
+    engine.input.tracking = true;
+    engine.input_source = InputSource::Recording {
+        writer,
+        seed,
+        error: None,
+    };
+    Ok(())
+}
+
+/// Stops recording and flushes the file.
+///
+/// Returns the first error that occurred while writing frames. Does nothing if not recording.
+pub fn stop_recording(engine: &mut Engine) -> io::Result<()> {
+    if !matches!(engine.input_source, InputSource::Recording { .. }) {
+        return Ok(());
+    }
+    match std::mem::take(&mut engine.input_source) {
+        InputSource::Recording {
+            mut writer, error, ..
+        } => match error {
+            Some(error) => Err(error),
+            None => writer.flush(),
+        },
+        _ => Ok(()),
+    }
+}
+
+/// Whether input is currently being recorded.
+pub fn is_recording(engine: &Engine) -> bool {
+    matches!(engine.input_source, InputSource::Recording { .. })
+}
+
+/// Replays `recording` in place of the terminal input, starting with the next frame.
+///
+/// Every [`start_frame`](crate::engine::start_frame) takes the `delta_time` and events of the next recorded frame
+/// instead of waiting for the FPS limiter and polling the terminal. Once all frames are replayed,
+/// input is taken from the terminal again.
+///
+/// Reseeds the particle system with the seed of the recording, so replaying should start at the same point
+/// of the program the recording was started at.
+///
+/// # Example
+/// ```rust,no_run
+/// # use germterm::{engine::{Engine, end_frame, init, start_frame}, input::recording::{is_replaying, load_recording, start_replay}};
+/// let mut engine = Engine::new(40, 20).headless(true);
+/// init(&mut engine).unwrap();
+/// start_replay(&mut engine, load_recording("session.rec").unwrap());
+///
+/// while is_replaying(&engine) {
+///     start_frame(&mut engine);
+///     // Update and draw
+///     end_frame(&mut engine).unwrap();
+/// }
+/// ```
+pub fn start_replay(engine: &mut Engine, recording: InputRecording) {
+    seed_particle_rng(engine, recording.seed);
+    engine.input.tracking = true;
+    engine.input.keyboard_enhanced = recording.keyboard_enhanced;
+    engine.input.pixel_mouse = recording.cell_pixel_size.is_some();
+    if let Some(cell_pixel_size) = recording.cell_pixel_size {
+        engine.input.cell_pixel_size = cell_pixel_size;
+    }
+    engine.input_source = InputSource::Replay {
+        recording,
+        next_frame: 0,
+    };
+}
+
+/// Whether recorded frames are left to be replayed.
+pub fn is_replaying(engine: &Engine) -> bool {
+    match &engine.input_source {
+        InputSource::Replay {
+            recording,
+            next_frame,
+        } => *next_frame < recording.frames.len(),
+        _ => false,
+    }
+}
+
+/// The seed of the recording being recorded or replayed.
+///
+/// Seeding the game's own random number generators with it keeps replays deterministic.
+pub fn recording_seed(engine: &Engine) -> Option<u64> {
+    match &engine.input_source {
+        InputSource::Terminal => None,
+        InputSource::Recording { seed, .. } => Some(*seed),
+        InputSource::Replay { recording, .. } => Some(recording.seed),
+    }
+}
+
+/// Takes the next recorded frame while replaying.
+pub(crate) fn next_replay_frame(source: &mut InputSource) -> Option<RecordedFrame> {
+    let InputSource::Replay {
+        recording,
+        next_frame,
+    } = source
+    else {
+        return None;
+    };
+
+    match recording.frames.get_mut(*next_frame) {
+        Some(frame) => {
+            *next_frame += 1;
+            Some(std::mem::take(frame))
+        }
+        None => {
+            *source = InputSource::Terminal;
+            None
+        }
+    }
+}
+
+/// Appends a frame to the recording, if recording.
+pub(crate) fn record_frame(source: &mut InputSource, frame: &RecordedFrame) {
+    let InputSource::Recording { writer, error, .. } = source else {
+        return;
+    };
+    if error.is_some() {
+        return;
+    }
+
+    let mut entry: String = String::new();
+    let _ = write_frame(&mut entry, frame);
+    if let Err(err) = writer
+        .write_all(entry.as_bytes())
+        .and_then(|_| writer.flush())
+    {
+        *error = Some(err);
+    }
+}
+
+fn recorded_cell_pixel_size(engine: &Engine) -> Option<(f32, f32)> {
+    engine
+        .input
+        .pixel_mouse
+        .then_some(engine.input.cell_pixel_size)
+}
+
+/// An error produced when parsing a recording.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordingParseError {
+    /// 1-based line number.
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for RecordingParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for RecordingParseError {}
+
+/// Parses an [`InputRecording`] from the format described in the [module docs](self).
+///
+/// # Example
+/// ```rust
+/// # use germterm::{crossterm::event::Event, input::recording::parse_recording};
+/// let recording = parse_recording("germterm-recording 1\nseed 7\nframe 3c888889\nresize 80 24\n").unwrap();
+/// assert_eq!(recording.seed, 7);
+/// assert_eq!(recording.frames[0].events, vec![Event::Resize(80, 24)]);
+/// ```
+pub fn parse_recording(source: &str) -> Result<InputRecording, RecordingParseError> {
+    let mut recording: InputRecording = InputRecording::default();
+    let mut lines = source
+        .lines()
+        .enumerate()
+        .map(|(index, line)| (index + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty());
+
+    match lines.next() {
+        Some((_, HEADER)) => {}
+        other => {
+            return Err(RecordingParseError {
+                line: other.map_or(1, |(line, _)| line),
+                message: format!("expected `{HEADER}`"),
+            });
+        }
+    }
+
+    for (line, entry) in lines {
+        let error = |message: String| RecordingParseError { line, message };
+        let mut fields = entry.split_ascii_whitespace();
+        let name: &str = fields.next().unwrap_or_default();
+        let fields: Vec<&str> = fields.collect();
+
+        match (name, fields.as_slice()) {
+            ("seed", [seed]) => {
+                recording.seed = seed
+                    .parse()
+                    .map_err(|_| error(format!("invalid seed `{seed}`")))?;
+            }
+            ("keyboard_enhanced", [value]) => {
+                recording.keyboard_enhanced = value
+                    .parse()
+                    .map_err(|_| error(format!("invalid bool `{value}`")))?;
+            }
+            ("cell_pixel_size", [width, height]) => {
+                let cell_pixel_size: Option<(f32, f32)> = Some((
+                    parse_f32(width).map_err(error)?,
+                    parse_f32(height).map_err(error)?,
+                ));
+                match recording.frames.last_mut() {
+                    Some(frame) => frame.cell_pixel_size = cell_pixel_size,
+                    None => recording.cell_pixel_size = cell_pixel_size,
+                }
+            }
+            ("frame", [delta_time]) => recording.frames.push(RecordedFrame {
+                delta_time: parse_f32(delta_time).map_err(error)?,
+                events: Vec::new(),
+                cell_pixel_size: None,
+            }),
+            _ => {
+                let event: Event = parse_event(name, &fields).map_err(error)?;
+                recording
+                    .frames
+                    .last_mut()
+                    .ok_or_else(|| error("event before the first frame".to_string()))?
+                    .events
+                    .push(event);
+            }
+        }
+    }
+
+    Ok(recording)
+}
+
+/// Formats an [`InputRecording`] into the format described in the [module docs](self).
+///
+/// Key events of keys without a name, which newer versions of crossterm might add, are left out.
+pub fn format_recording(recording: &InputRecording) -> String {
+    let mut output: String = String::new();
+    let _ = write_header(
+        &mut output,
+        recording.seed,
+        recording.keyboard_enhanced,
+        recording.cell_pixel_size,
+    );
+    for frame in &recording.frames {
+        let _ = write_frame(&mut output, frame);
+    }
+    output
+}
+
+/// Loads an [`InputRecording`] from a file.
+///
+/// Parse errors are returned as [`io::ErrorKind::InvalidData`].
+pub fn load_recording(path: impl AsRef<Path>) -> io::Result<InputRecording> {
+    let source: String = fs::read_to_string(path)?;
+    parse_recording(&source).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+/// Saves an [`InputRecording`] to a file, replacing its contents.
+pub fn save_recording(recording: &InputRecording, path: impl AsRef<Path>) -> io::Result<()> {
+    fs::write(path, format_recording(recording))
+}
+
+fn write_header(
+    output: &mut String,
+    seed: u64,
+    keyboard_enhanced: bool,
+    cell_pixel_size: Option<(f32, f32)>,
+) -> fmt::Result {
+    writeln!(output, "{HEADER}")?;
+    writeln!(output, "seed {seed}")?;
+    writeln!(output, "keyboard_enhanced {keyboard_enhanced}")?;
+    if let Some(cell_pixel_size) = cell_pixel_size {
+        write_cell_pixel_size(output, cell_pixel_size)?;
+    }
+    Ok(())
+}
+
+fn write_cell_pixel_size(output: &mut String, (width, height): (f32, f32)) -> fmt::Result {
+    writeln!(
+        output,
+        "cell_pixel_size {:08x} {:08x}",
+        width.to_bits(),
+        height.to_bits()
+    )
+}
+
+fn write_frame(output: &mut String, frame: &RecordedFrame) -> fmt::Result {
+    writeln!(output, "frame {:08x}", frame.delta_time.to_bits())?;
+    let mut cell_pixel_size_written: bool = false;
+    for event in &frame.events {
+        match event {
+            Event::Key(KeyEvent {
+                code,
+                modifiers,
+                kind,
+                state,
+            }) => {
+                let Some(code) = key_name(*code) else {
+                    continue;
+                };
+                writeln!(
+                    output,
+                    "key {code} {} {} {}",
+                    modifiers.bits(),
+                    key_kind_name(*kind),
+                    state.bits()
+                )?;
+            }
+            Event::Mouse(MouseEvent {
+                kind,
+                column,
+                row,
+                modifiers,
+            }) => writeln!(
+                output,
+                "mouse {} {column} {row} {}",
+                mouse_kind_name(*kind),
+                modifiers.bits()
+            )?,
+            Event::Resize(cols, rows) => {
+                writeln!(output, "resize {cols} {rows}")?;
+                if let Some(cell_pixel_size) = frame.cell_pixel_size {
+                    write_cell_pixel_size(output, cell_pixel_size)?;
+                    cell_pixel_size_written = true;
+                }
+            }
+            Event::FocusGained => writeln!(output, "focus_gained")?,
+            Event::FocusLost => writeln!(output, "focus_lost")?,
+            Event::Paste(text) => {
+                output.push_str("paste ");
+                for byte in text.bytes() {
+                    write!(output, "{byte:02x}")?;
+                }
+                output.push('\n');
+            }
+        }
+    }
+    if let Some(cell_pixel_size) = frame.cell_pixel_size
+        && !cell_pixel_size_written
+    {
+        write_cell_pixel_size(output, cell_pixel_size)?;
+    }
+    Ok(())
+}
+
+fn parse_event(name: &str, fields: &[&str]) -> Result<Event, String> {
+    let event: Event = match (name, fields) {
+        ("key", [code, modifiers, kind, state]) => Event::Key(KeyEvent {
+            code: parse_key(code).ok_or_else(|| format!("unknown key `{code}`"))?,
+            modifiers: KeyModifiers::from_bits_retain(parse_number(modifiers)?),
+            kind: parse_key_kind(kind).ok_or_else(|| format!("unknown key kind `{kind}`"))?,
+            state: KeyEventState::from_bits_retain(parse_number(state)?),
+        }),
+        ("mouse", [kind, column, row, modifiers]) => Event::Mouse(MouseEvent {
+            kind: parse_mouse_kind(kind).ok_or_else(|| format!("unknown mouse kind `{kind}`"))?,
+            column: parse_number(column)?,
+            row: parse_number(row)?,
+            modifiers: KeyModifiers::from_bits_retain(parse_number(modifiers)?),
+        }),
+        ("resize", [cols, rows]) => Event::Resize(parse_number(cols)?, parse_number(rows)?),
+        ("focus_gained", []) => Event::FocusGained,
+        ("focus_lost", []) => Event::FocusLost,
+        ("paste", hex) => {
+            let hex: &str = hex.first().copied().unwrap_or_default();
+            let bytes: Vec<u8> = (0..hex.len())
+                .step_by(2)
+                .map(|i| {
+                    hex.get(i..i + 2)
+                        .and_then(|byte| u8::from_str_radix(byte, 16).ok())
+                })
+                .collect::<Option<_>>()
+                .ok_or_else(|| format!("invalid paste `{hex}`"))?;
+            Event::Paste(String::from_utf8(bytes).map_err(|_| "paste isn't UTF-8".to_string())?)
+        }
+        _ => return Err(format!("unknown entry `{name}`")),
+    };
+    Ok(event)
+}
+
+fn parse_number<T: std::str::FromStr>(value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("invalid number `{value}`"))
+}
+
+fn parse_f32(value: &str) -> Result<f32, String> {
+    u32::from_str_radix(value, 16)
+        .map(f32::from_bits)
+        .map_err(|_| format!("invalid float bits `{value}`"))
+}
+
+fn key_kind_name(kind: KeyEventKind) -> &'static str {
+    match kind {
+        KeyEventKind::Press => "press",
+        KeyEventKind::Repeat => "repeat",
+        KeyEventKind::Release => "release",
+    }
+}
+
+fn parse_key_kind(name: &str) -> Option<KeyEventKind> {
+    match name {
+        "press" => Some(KeyEventKind::Press),
+        "repeat" => Some(KeyEventKind::Repeat),
+        "release" => Some(KeyEventKind::Release),
+        _ => None,
+    }
+}
+
+fn mouse_kind_name(kind: MouseEventKind) -> String {
+    let button_name = |button: MouseButton| match button {
+        MouseButton::Left => "left",
+        MouseButton::Right => "right",
+        MouseButton::Middle => "middle",
+    };
+    match kind {
+        MouseEventKind::Down(button) => format!("down:{}", button_name(button)),
+        MouseEventKind::Up(button) => format!("up:{}", button_name(button)),
+        MouseEventKind::Drag(button) => format!("drag:{}", button_name(button)),
+        MouseEventKind::Moved => "moved".to_string(),
+        MouseEventKind::ScrollDown => "scroll_down".to_string(),
+        MouseEventKind::ScrollUp => "scroll_up".to_string(),
+        MouseEventKind::ScrollLeft => "scroll_left".to_string(),
+        MouseEventKind::ScrollRight => "scroll_right".to_string(),
+    }
+}
+
+fn parse_mouse_kind(name: &str) -> Option<MouseEventKind> {
+    match name {
+        "moved" => return Some(MouseEventKind::Moved),
+        "scroll_down" => return Some(MouseEventKind::ScrollDown),
+        "scroll_up" => return Some(MouseEventKind::ScrollUp),
+        "scroll_left" => return Some(MouseEventKind::ScrollLeft),
+        "scroll_right" => return Some(MouseEventKind::ScrollRight),
+        _ => {}
+    }
+
+    let (kind, button) = name.split_once(':')?;
+    let button: MouseButton = match button {
+        "left" => MouseButton::Left,
+        "right" => MouseButton::Right,
+        "middle" => MouseButton::Middle,
+        _ => return None,
+    };
+    match kind {
+        "down" => Some(MouseEventKind::Down(button)),
+        "up" => Some(MouseEventKind::Up(button)),
+        "drag" => Some(MouseEventKind::Drag(button)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::{KeyCode, MediaKeyCode};
+
+    fn key(code: KeyCode, kind: KeyEventKind) -> Event {
+        Event::Key(KeyEvent {
+            code,
+            modifiers: KeyModifiers::CONTROL | KeyModifiers::SHIFT,
+            kind,
+            state: KeyEventState::NONE,
+        })
+    }
+
+    fn mouse(kind: MouseEventKind, column: u16, row: u16) -> Event {
+        Event::Mouse(MouseEvent {
+            kind,
+            column,
+            row,
+            modifiers: KeyModifiers::ALT,
+        })
+    }
+
+    #[test]
+    fn formatted_recordings_parse_back() {
+        let recording = InputRecording {
+            seed: u64::MAX - 7,
+            keyboard_enhanced: true,
+            cell_pixel_size: Some((9.5, 19.25)),
+            frames: vec![
+                RecordedFrame {
+                    delta_time: 1.0 / 60.0,
+                    events: vec![
+                        key(KeyCode::Char('w'), KeyEventKind::Press),
+                        key(KeyCode::Char('é'), KeyEventKind::Repeat),
+                        key(KeyCode::F(12), KeyEventKind::Release),
+                        key(KeyCode::PageDown, KeyEventKind::Press),
+                        key(KeyCode::KeypadBegin, KeyEventKind::Press),
+                        key(KeyCode::Char('W'), KeyEventKind::Press),
+                        key(KeyCode::Char(' '), KeyEventKind::Press),
+                        key(KeyCode::Char('\t'), KeyEventKind::Press),
+                        key(KeyCode::Media(MediaKeyCode::PlayPause), KeyEventKind::Press),
+                    ],
+                    cell_pixel_size: None,
+                },
+                RecordedFrame {
+                    delta_time: 0.1 + 0.2,
+                    events: vec![],
+                    cell_pixel_size: None,
+                },
+                RecordedFrame {
+                    delta_time: f32::MIN_POSITIVE,
+                    events: vec![
+                        mouse(MouseEventKind::Down(MouseButton::Left), 12, 4),
+                        mouse(MouseEventKind::Drag(MouseButton::Middle), 13, 5),
+                        mouse(MouseEventKind::Up(MouseButton::Right), 0, 0),
+                        mouse(MouseEventKind::Moved, 65535, 1),
+                        mouse(MouseEventKind::ScrollLeft, 3, 3),
+                        Event::Resize(80, 24),
+                        Event::FocusGained,
+                        Event::FocusLost,
+                        Event::Paste("multi\nline ✓ paste".to_string()),
+                        Event::Paste(String::new()),
+                    ],
+                    cell_pixel_size: None,
+                },
+            ],
+        };
+
+        assert_eq!(
+            parse_recording(&format_recording(&recording)),
+            Ok(recording)
+        );
+    }
+
+    #[test]
+    fn recordings_without_pixel_size_parse_back() {
+        let recording = InputRecording {
+            seed: 42,
+            keyboard_enhanced: false,
+            cell_pixel_size: None,
+            frames: vec![RecordedFrame {
+                delta_time: 0.016,
+                events: vec![key(KeyCode::Esc, KeyEventKind::Press)],
+                cell_pixel_size: None,
+            }],
+        };
+
+        assert_eq!(
+            parse_recording(&format_recording(&recording)),
+            Ok(recording)
+        );
+    }
+
+    #[test]
+    fn cell_pixel_sizes_after_resizes_parse_back() {
+        let recording = InputRecording {
+            seed: 3,
+            keyboard_enhanced: false,
+            cell_pixel_size: Some((8.0, 16.0)),
+            frames: vec![
+                RecordedFrame {
+                    delta_time: 0.016,
+                    events: vec![Event::Resize(100, 30), mouse(MouseEventKind::Moved, 40, 80)],
+                    cell_pixel_size: Some((10.0, 20.0)),
+                },
+                RecordedFrame {
+                    delta_time: 0.016,
+                    events: vec![mouse(MouseEventKind::Moved, 40, 80)],
+                    cell_pixel_size: None,
+                },
+            ],
+        };
+
+        let formatted: String = format_recording(&recording);
+        assert!(formatted.contains("resize 100 30\ncell_pixel_size 41200000 41a00000\n"));
+        assert_eq!(parse_recording(&formatted), Ok(recording));
+    }
+
+    #[test]
+    fn replays_take_cell_pixel_sizes_from_the_recording() {
+        let mut engine = Engine::new(40, 20).headless(true);
+        crate::engine::init(&mut engine).unwrap();
+        start_replay(
+            &mut engine,
+            InputRecording {
+                cell_pixel_size: Some((8.0, 16.0)),
+                frames: vec![
+                    RecordedFrame {
+                        delta_time: 0.016,
+                        events: vec![mouse(MouseEventKind::Moved, 40, 80)],
+                        cell_pixel_size: None,
+                    },
+                    RecordedFrame {
+                        delta_time: 0.016,
+                        events: vec![Event::Resize(100, 30), mouse(MouseEventKind::Moved, 40, 80)],
+                        cell_pixel_size: Some((10.0, 20.0)),
+                    },
+                ],
+                ..Default::default()
+            },
+        );
+
+        crate::engine::start_frame(&mut engine);
+        assert_eq!(engine.input.mouse_position(), Some((5.0, 5.0)));
+        crate::engine::start_frame(&mut engine);
+        assert_eq!(engine.input.mouse_position(), Some((4.0, 4.0)));
+    }
+
+    #[test]
+    fn replays_feed_recorded_frames_into_the_engine() {
+        let mut engine = Engine::new(40, 20).headless(true);
+        crate::engine::init(&mut engine).unwrap();
+        let events: Vec<Event> = vec![key(KeyCode::Char('w'), KeyEventKind::Press)];
+        start_replay(
+            &mut engine,
+            InputRecording {
+                seed: 7,
+                frames: vec![
+                    RecordedFrame {
+                        delta_time: 0.25,
+                        events: events.clone(),
+                        cell_pixel_size: None,
+                    },
+                    RecordedFrame {
+                        delta_time: 0.5,
+                        events: vec![],
+                        cell_pixel_size: None,
+                    },
+                ],
+                ..Default::default()
+            },
+        );
+        assert_eq!(recording_seed(&engine), Some(7));
+
+        crate::engine::start_frame(&mut engine);
+        assert_eq!(engine.delta_time, 0.25);
+        assert_eq!(engine.input.events(), events.as_slice());
+        assert!(is_replaying(&engine));
+
+        crate::engine::start_frame(&mut engine);
+        assert_eq!(engine.delta_time, 0.5);
+        assert!(engine.input.events().is_empty());
+        assert!(!is_replaying(&engine));
+    }
+
+    #[test]
+    fn errors_point_at_the_offending_line() {
+        assert_eq!(parse_recording("not a recording").unwrap_err().line, 1);
+
+        let source: String =
+            format!("{HEADER}\nseed 1\nkeyboard_enhanced false\nframe 3c888889\nteleport 1 2\n");
+        assert_eq!(parse_recording(&source).unwrap_err().line, 5);
+    }
+}
@@ -5,7 +5,7 @@ pub fn create_layer(engine: &mut Engine, index: usize) -> LayerIndex {
     LayerIndex(index)
 }
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct LayerIndex(pub(crate) usize);
 
 pub struct Layer(pub(crate) Vec<DrawCall>);
@@ -2,14 +2,20 @@
 
 pub use crossterm;
 
+pub mod app;
 pub mod cell;
 pub mod color;
 pub mod draw;
+pub mod easing;
 pub mod engine;
+pub mod fixed_timestep;
 pub mod fps_counter;
 pub mod fps_limiter;
 pub mod frame;
 pub mod input;
 pub mod layer;
 pub mod particle;
+pub mod particle_effect;
 pub mod rich_text;
+pub mod timer;
+pub mod tween;
@@ -1,68 +1,512 @@
-//! Octad-based particle system.
+//! Particle system.
 //!
 //! This module provides a way of spawning particles using the [`spawn_particles`] function.
 //! Particles are automatically updated and drawn at the end of the frame.
 //!
+//! For effects that keep spawning particles over time, such as fire, smoke or rain,
+//! a continuous emitter can be started using [`start_emitter`].
+//!
 //! The particles and their behaviors can be customized using [`ParticleSpec`] and [`ParticleEmitter`].
 //! The system uses approximated velocity, gravity and drag calculations.
 //!
+//! Alpha, speed, gravity and size can change over the lifetime of a particle
+//! using curves set in [`ParticleSpec::curves`].
+//!
+//! Particles are drawn as octads by default. Other drawing formats, characters and small sprites
+//! can be chosen per spec using [`ParticleSpec::render`].
+//!
+//! Moving particles can leave a fading streak behind them using [`ParticleSpec::trail`].
+//!
+//! ## Physics
+//! World gravity and the cell aspect ratio are configured per engine using [`set_particle_gravity`]
+//! and [`set_cell_aspect_ratio`]. On top of that, [`ForceField`]s such as wind, attractors, vortices
+//! and turbulence can be added with [`add_force_field`], which affect every alive particle.
+//!
+//! ## Collisions
+//! Particles can collide with the screen bounds and with solid regions, registered using
+//! [`add_solid_rect`] or [`set_solid_mask`]. What happens on collision is decided per spec
+//! by [`ParticleSpec::collision`].
+//!
+//! ## Sub-emitters and events
+//! Particles can spawn child effects when they die or collide, set with [`ParticleSpec::on_death`]
+//! and [`ParticleSpec::on_collision`]. Deaths and collisions of particles with [`ParticleSpec::event_tag`]
+//! set can be observed using [`particle_events`].
+//!
+//! ## Management
+//! Alive particles can be removed using [`clear_particles`] and [`clear_particles_on_layer`],
+//! for example on scene transitions. The amount of alive particles can be capped with [`set_particle_budget`].
+//!
 //! ## Notes
 //! Particles are always drawn at the end of the frame. This means they'll always be drawn last on the specified layer.
 //! If you wish to spawn particles underneath other drawn elements, you can create a new layer with a lower index and draw to it.
 
-use std::{f32::consts::PI, ops::RangeInclusive};
+use std::{collections::VecDeque, f32::consts::PI, ops::RangeInclusive, sync::Arc};
 
-use rand::{Rng, rngs::ThreadRng};
+use bitflags::bitflags;
+use rand::{Rng, RngCore, SeedableRng, rngs::StdRng};
 
 use crate::{
     color::{Color, ColorGradient, sample_gradient},
-    draw::draw_octad,
+    draw::{draw_blocktad, draw_horizontal_twoxel, draw_octad, draw_text, draw_twoxel},
+    easing::{Curve, sample_curve},
     engine::Engine,
+    fixed_timestep::interpolate,
     layer::LayerIndex,
+    rich_text::RichText,
 };
 
+/// The area particles are spawned in, relative to the emitter position.
+///
+/// Sizes and offsets are in cells.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ParticleEmitterShape {
+    /// Spawns from a single point in every direction.
     Circle,
+    /// Spawns from a single point within a cone. Ignores [`ParticleEmitter::direction`].
     Cone { direction_deg: f32, width_deg: f32 },
+    /// Spawns inside a rectangle centered on the emitter, or only along its outline if `edge_only` is set.
+    Rect {
+        width: f32,
+        height: f32,
+        edge_only: bool,
+    },
+    /// Spawns along a line segment from the emitter to the emitter offset by `dx`, `dy`.
+    Line { dx: f32, dy: f32 },
+    /// Spawns between two circles centered on the emitter.
+    ///
+    /// The radii are measured horizontally. The vertical radii are scaled by the cell aspect ratio,
+    /// so the ring appears round.
+    Ring {
+        inner_radius: f32,
+        outer_radius: f32,
+    },
+    /// Spawns at a randomly picked point of the set, each point being an offset from the emitter.
+    Points(Arc<Vec<(f32, f32)>>),
+}
+
+/// The direction spawned particles move in.
+#[derive(Clone, Copy, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum EmitterDirection {
+    /// Away from the emitter position. Particles spawned exactly at the emitter position move in a random direction.
+    #[default]
+    Radial,
+    /// Perpendicular to the shape at the spawn point, pointing outwards.
+    /// Falls back to [`EmitterDirection::Radial`] for shapes without an outline, like [`ParticleEmitterShape::Points`].
+    Normal,
+    /// Towards `direction_deg`, randomly spread within `spread_deg`.
+    Fixed { direction_deg: f32, spread_deg: f32 },
 }
 
+/// The color of spawned particles.
+///
+/// Weighted variants pick a random entry per particle, with the chance of each entry
+/// being proportional to its paired weight.
+/// Entries with a weight of `0.0` or less are never picked.
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ParticleColor {
     Solid(Color),
     Gradient(ColorGradient),
+    /// Needs at least 1 entry with a positive weight, otherwise particles fall back to [`Color::WHITE`].
+    /// Sets without one are rejected when deserialized.
+    WeightedSolid(
+        #[cfg_attr(feature = "serde", serde(deserialize_with = "deserialize_weighted"))]
+        Vec<(Color, f32)>,
+    ),
+    /// Needs at least 1 entry with a positive weight, otherwise particles fall back to [`Color::WHITE`].
+    /// Sets without one are rejected when deserialized.
+    WeightedGradient(
+        #[cfg_attr(feature = "serde", serde(deserialize_with = "deserialize_weighted"))]
+        Vec<(ColorGradient, f32)>,
+    ),
+}
+
+#[cfg(feature = "serde")]
+fn deserialize_range<'de, D>(deserializer: D) -> Result<RangeInclusive<f32>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let range: RangeInclusive<f32> = serde::Deserialize::deserialize(deserializer)?;
+    let (start, end) = (*range.start(), *range.end());
+    if !start.is_finite() || !end.is_finite() {
+        return Err(serde::de::Error::custom(format!(
+            "range bounds must be finite, got {start}..={end}"
+        )));
+    }
+    if start > end {
+        return Err(serde::de::Error::custom(format!(
+            "range start must not be greater than its end, got {start}..={end}"
+        )));
+    }
+    Ok(range)
+}
+
+#[cfg(feature = "serde")]
+fn deserialize_weighted<'de, D, T>(deserializer: D) -> Result<Vec<(T, f32)>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: serde::Deserialize<'de>,
+{
+    let items: Vec<(T, f32)> = serde::Deserialize::deserialize(deserializer)?;
+    if !items.iter().any(|(_, weight)| *weight > 0.0) {
+        return Err(serde::de::Error::custom(
+            "weighted set must have at least 1 entry with a positive weight",
+        ));
+    }
+    Ok(items)
+}
+
+/// How particles are drawn.
+///
+/// All variants are drawn using the particle's color as the `fg` color.
+#[derive(Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ParticleRender {
+    /// Drawn with [`draw_octad`].
+    #[default]
+    Octad,
+    /// Drawn with [`draw_blocktad`].
+    Blocktad,
+    /// Drawn with [`draw_twoxel`].
+    Twoxel,
+    /// Drawn with [`draw_horizontal_twoxel`].
+    HorizontalTwoxel,
+    /// Drawn as a single standard character.
+    Char(char),
+    /// Drawn as a standard character picked from the sequence based on the particle's lifetime,
+    /// eg. `['*', '+', '.']` starts as `*` and ends as `.`.
+    CharSequence(Arc<Vec<char>>),
+    /// Drawn as a small multi-line sprite of standard characters, centered on the particle's position.
+    ///
+    /// Spaces are left transparent.
+    Sprite(Arc<Vec<String>>),
+}
+
+/// A child effect spawned by a particle when it dies or collides.
+///
+/// # Example
+/// ```rust,no_run
+/// # use std::sync::Arc;
+/// # use germterm::particle::{ParticleEmitter, ParticleSpec, SubEmitter};
+/// // A firework shell bursting into a circle of sparks when it dies
+/// let shell = ParticleSpec {
+///     lifetime_sec: 1.0..=1.2,
+///     on_death: Some(Arc::new(SubEmitter {
+///         spec: ParticleSpec::default(),
+///         emitter: ParticleEmitter::default(),
+///         inherit_velocity: 0.2,
+///     })),
+///     ..Default::default()
+/// };
+/// ```
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SubEmitter {
+    pub spec: ParticleSpec,
+    pub emitter: ParticleEmitter,
+    /// Fraction of the parent particle's velocity added to every spawned child particle.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub inherit_velocity: f32,
+}
+
+/// What happened to a particle in a [`ParticleEvent`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParticleEventKind {
+    /// The particle reached the end of its lifetime.
+    Death,
+    /// The particle collided, including collisions killing it with [`ParticleCollision::Kill`].
+    Collision,
+}
+
+/// A death or collision of a particle spawned with [`ParticleSpec::event_tag`] set.
+///
+/// Read using [`particle_events`].
+#[derive(Clone, Copy, Debug)]
+pub struct ParticleEvent {
+    pub kind: ParticleEventKind,
+    /// The [`ParticleSpec::event_tag`] of the particle.
+    pub tag: u32,
+    pub x: f32,
+    pub y: f32,
+    pub velocity: (f32, f32),
+    pub layer_index: LayerIndex,
+}
+
+/// A streak left behind a moving particle.
+///
+/// The last `length` positions of the particle are retained and drawn behind it
+/// in its render format, fading out towards the end of the trail.
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ParticleTrail {
+    /// Number of previous positions drawn behind the particle, one per simulation step.
+    ///
+    /// Without the [fixed timestep](crate::fixed_timestep), particles are simulated once per frame,
+    /// so the trail covers the last `length` frames. With it enabled, the trail covers the last `length` fixed steps
+    /// instead, spanning the same amount of time regardless of the frame rate. Trail positions are interpolated
+    /// between steps like the particle itself, so they stay whole steps behind it.
+    pub length: usize,
+    /// Alpha multiplier of the trail position right behind the particle.
+    /// Positions further back fade out linearly to fully transparent.
+    pub start_alpha: f32,
+}
+
+impl Default for ParticleTrail {
+    fn default() -> Self {
+        Self {
+            length: 6,
+            start_alpha: 0.8,
+        }
+    }
+}
+
+/// A sub-emitter waiting to be spawned at the end of the particle update.
+pub(crate) struct PendingSubEmitter {
+    sub_emitter: Arc<SubEmitter>,
+    origin: SpawnOrigin,
+}
+
+/// Properties of a particle that change over its lifetime.
+///
+/// Each curve is sampled with a normalized lifetime going from `0.0` at spawn to `1.0` at death.
+/// `None` leaves the property unaffected.
+#[derive(Clone, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct ParticleCurves {
+    /// Multiplies the alpha of the particle's color.
+    pub alpha: Option<Curve>,
+    /// Multiplies the distance the particle travels.
+    pub speed: Option<Curve>,
+    /// Multiplies the gravity affecting the particle.
+    pub gravity: Option<Curve>,
+    /// Size of the particle in units of its render format, eg. `3.0` draws a round cluster
+    /// 3 octads wide, or 3 characters wide for characters. Has no effect on sprites.
+    pub size: Option<Curve>,
+}
+
+impl ParticleCurves {
+    fn is_empty(&self) -> bool {
+        self.alpha.is_none()
+            && self.speed.is_none()
+            && self.gravity.is_none()
+            && self.size.is_none()
+    }
+}
+
+/// [`ParticleColor`] resolved for a single particle.
+enum ParticleStateColor {
+    Solid(Color),
+    Gradient(ColorGradient),
 }
 
 pub(crate) struct ParticleState {
     pos: (f32, f32),
+    // Position before the last simulation step, used for interpolation
+    prev_pos: (f32, f32),
     velocity: (f32, f32),
-    color: ParticleColor,
+    color: ParticleStateColor,
+    render: ParticleRender,
+    curves: Option<Arc<ParticleCurves>>,
     gravity_scale: f32,
+    gravity: Option<(f32, f32)>,
+    drag: f32,
+    collision: ParticleCollision,
+    collide_with: CollisionTargets,
+    stuck: bool,
+    trail: Option<ParticleTrail>,
+    // Previous positions, most recent first
+    trail_positions: VecDeque<(f32, f32)>,
+    on_death: Option<Arc<SubEmitter>>,
+    on_collision: Option<Arc<SubEmitter>>,
+    event_tag: Option<u32>,
     spawn_timestamp: f32,
     death_timestamp: f32,
     layer_index: LayerIndex,
 }
 
+/// Describes the particles spawned by an emitter.
+///
+/// Range fields are sampled randomly for every spawned particle.
+/// Use a range with equal bounds (eg. `1.0..=1.0`) for a fixed value.
+/// Reversed ranges are sampled as if their bounds were swapped, and are rejected when deserialized.
+#[derive(Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct ParticleSpec {
-    // TODO: Make this also support a weighted set of colors
     pub color: ParticleColor,
+    pub render: ParticleRender,
+    pub curves: ParticleCurves,
+    #[cfg_attr(feature = "serde", serde(deserialize_with = "deserialize_range"))]
     pub speed: RangeInclusive<f32>,
-    pub lifetime_sec: f32,
-    pub gravity_scale: f32,
+    #[cfg_attr(feature = "serde", serde(deserialize_with = "deserialize_range"))]
+    pub lifetime_sec: RangeInclusive<f32>,
+    #[cfg_attr(feature = "serde", serde(deserialize_with = "deserialize_range"))]
+    pub gravity_scale: RangeInclusive<f32>,
+    /// Overrides the world gravity vector for these particles. `None` uses the world gravity set with [`set_particle_gravity`].
+    pub gravity: Option<(f32, f32)>,
+    #[cfg_attr(feature = "serde", serde(deserialize_with = "deserialize_range"))]
+    pub drag: RangeInclusive<f32>,
+    /// What happens when a particle collides with anything in [`ParticleSpec::collide_with`].
+    pub collision: ParticleCollision,
+    pub collide_with: CollisionTargets,
+    pub trail: Option<ParticleTrail>,
+    /// Child effect spawned where a particle dies at the end of its lifetime.
+    ///
+    /// Not spawned for particles killed by a collision with [`ParticleCollision::Kill`], which spawn
+    /// [`ParticleSpec::on_collision`] instead.
+    pub on_death: Option<Arc<SubEmitter>>,
+    /// Child effect spawned where a particle collides.
+    pub on_collision: Option<Arc<SubEmitter>>,
+    /// When set, deaths and collisions of these particles are reported through [`particle_events`]
+    /// along with this tag.
+    pub event_tag: Option<u32>,
 }
 
 impl Default for ParticleSpec {
     fn default() -> Self {
         Self {
             color: ParticleColor::Solid(Color::WHITE),
+            render: ParticleRender::Octad,
+            curves: ParticleCurves::default(),
             speed: 15.0..=30.0,
-            lifetime_sec: 3.0,
-            gravity_scale: 1.0,
+            lifetime_sec: 3.0..=3.0,
+            gravity_scale: 1.0..=1.0,
+            gravity: None,
+            drag: 3.0..=3.0,
+            collision: ParticleCollision::None,
+            collide_with: CollisionTargets::all(),
+            trail: None,
+            on_death: None,
+            on_collision: None,
+            event_tag: None,
+        }
+    }
+}
+
+/// What happens to a particle when it collides with the screen bounds or a solid region.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ParticleCollision {
+    /// Particles pass through everything.
+    #[default]
+    None,
+    /// Particles die on collision.
+    ///
+    /// The collision is reported as a [`ParticleEventKind::Collision`] event and spawns [`ParticleSpec::on_collision`],
+    /// without a [`ParticleEventKind::Death`] event or [`ParticleSpec::on_death`].
+    Kill,
+    /// Particles bounce off, keeping `restitution` of their velocity along the collision axis.
+    ///
+    /// A `restitution` of `1.0` results in a perfectly elastic bounce, `0.0` stops the particle along that axis.
+    /// Particles hitting too slowly to bounce, such as ones resting on a floor, come to rest along that axis
+    /// without reporting a collision.
+    Bounce { restitution: f32 },
+    /// Particles stop moving and stay in place until they die.
+    Stick,
+}
+
+bitflags! {
+    /// Things particles can collide with.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    #[cfg_attr(
+        feature = "serde",
+        derive(serde::Serialize, serde::Deserialize),
+        serde(transparent)
+    )]
+    pub struct CollisionTargets: u8 {
+        /// The edges of the frame.
+        const SCREEN_BOUNDS = 0b_00000001;
+        /// Solid rects added with [`add_solid_rect`] and the solid mask set with [`set_solid_mask`].
+        const SOLIDS        = 0b_00000010;
+    }
+}
+
+/// A grid of solid cells particles can collide with.
+///
+/// Each cell covers a single terminal cell, starting at the top left corner of the frame.
+#[derive(Clone)]
+pub struct SolidMask {
+    pub width: u16,
+    pub height: u16,
+    pub cells: Vec<bool>,
+}
+
+impl SolidMask {
+    /// Creates a new mask with no solid cells.
+    pub fn new(width: u16, height: u16) -> Self {
+        Self {
+            width,
+            height,
+            cells: vec![false; width as usize * height as usize],
+        }
+    }
+
+    /// Marks the cell at `x`, `y` as solid or empty. Out of bounds cells are ignored.
+    pub fn set(&mut self, x: u16, y: u16, solid: bool) {
+        if x < self.width && y < self.height {
+            self.cells[y as usize * self.width as usize + x as usize] = solid;
         }
     }
+
+    /// Returns `true` if the cell at `x`, `y` is solid. Out of bounds cells are never solid.
+    pub fn is_solid(&self, x: u16, y: u16) -> bool {
+        x < self.width
+            && y < self.height
+            && self.cells[y as usize * self.width as usize + x as usize]
+    }
+}
+
+/// A handle to a solid rect owned by the engine.
+///
+/// Returned by [`add_solid_rect`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SolidHandle(u64);
+
+#[derive(Clone, Copy)]
+pub(crate) struct SolidRect {
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+}
+
+/// What happens when spawning particles would exceed the [`ParticleBudget`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ParticleEvictionPolicy {
+    /// Removes the oldest alive particles to make room for new ones.
+    #[default]
+    DropOldest,
+    /// Doesn't spawn new particles until alive ones die.
+    RefuseNew,
 }
 
+/// Caps the amount of alive particles, set using [`set_particle_budget`].
+///
+/// Particles removed by the budget don't trigger their [`ParticleSpec::on_death`] or produce events.
+#[derive(Clone, Copy, Debug)]
+pub struct ParticleBudget {
+    pub max_particles: usize,
+    pub eviction: ParticleEvictionPolicy,
+}
+
+#[derive(Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct ParticleEmitter {
     pub shape: ParticleEmitterShape,
+    pub direction: EmitterDirection,
     pub count: usize,
 }
 
@@ -70,11 +514,99 @@ impl Default for ParticleEmitter {
     fn default() -> Self {
         Self {
             shape: ParticleEmitterShape::Circle,
+            direction: EmitterDirection::Radial,
             count: 25,
         }
     }
 }
 
+/// Timing of a continuous emitter started with [`start_emitter`].
+#[derive(Clone)]
+pub struct ContinuousEmitter {
+    /// Amount of particles spawned per second as a steady stream.
+    pub rate: f32,
+    /// Spawns a burst of [`ParticleEmitter::count`] particles every `burst_interval_sec` seconds, on top of `rate`.
+    ///
+    /// The first burst is spawned as soon as the emitter starts.
+    pub burst_interval_sec: Option<f32>,
+    /// How long the emitter stays alive for, in seconds. `None` keeps it alive until [`stop_emitter`] is called.
+    pub duration_sec: Option<f32>,
+}
+
+impl Default for ContinuousEmitter {
+    fn default() -> Self {
+        Self {
+            rate: 30.0,
+            burst_interval_sec: None,
+            duration_sec: None,
+        }
+    }
+}
+
+/// A force affecting the velocity of every alive particle.
+///
+/// Positions and radii are in the same coordinate space as the rest of the drawing API.
+/// Strengths are accelerations in the same units as gravity.
+#[derive(Clone)]
+pub enum ForceField {
+    /// A constant acceleration, eg. wind.
+    Wind { x: f32, y: f32 },
+    /// Pulls particles towards a point within `radius`.
+    /// A negative `strength` pushes particles away instead, turning it into a repulsor.
+    ///
+    /// The force linearly falls off towards the edge of the radius.
+    Attractor {
+        x: f32,
+        y: f32,
+        strength: f32,
+        radius: f32,
+    },
+    /// Swirls particles around a point within `radius`.
+    /// A positive `strength` swirls clockwise, a negative one counterclockwise.
+    ///
+    /// The force linearly falls off towards the edge of the radius.
+    Vortex {
+        x: f32,
+        y: f32,
+        strength: f32,
+        radius: f32,
+    },
+    /// Smoothly varying pseudo-random forces.
+    ///
+    /// `scale` controls how quickly the force changes across space,
+    /// `speed` controls how quickly it changes over time.
+    Turbulence {
+        strength: f32,
+        scale: f32,
+        speed: f32,
+    },
+}
+
+/// A handle to a force field owned by the engine.
+///
+/// Returned by [`add_force_field`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ForceFieldHandle(u64);
+
+/// A handle to a continuous emitter owned by the engine.
+///
+/// Returned by [`start_emitter`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EmitterHandle(u64);
+
+pub(crate) struct EmitterState {
+    handle: EmitterHandle,
+    layer_index: LayerIndex,
+    pos: (f32, f32),
+    spec: ParticleSpec,
+    emitter: ParticleEmitter,
+    continuous: ContinuousEmitter,
+    paused: bool,
+    elapsed_sec: f32,
+    spawn_accumulator: f32,
+    next_burst_sec: f32,
+}
+
 /// Spawns particles once at a position with specified parameters.
 ///
 /// Particles can be customized by tinkering with the `spec` and `emitter` parameters.
@@ -97,103 +629,2071 @@ pub fn spawn_particles(
     spec: &ParticleSpec,
     emitter: &ParticleEmitter,
 ) {
-    let mut rng: ThreadRng = rand::rng();
-
-    match emitter.shape {
-        ParticleEmitterShape::Circle => {
-            for _ in 0..emitter.count {
-                let angle: f32 = rng.random_range(0.0..=2.0 * PI);
-                let speed: f32 = rng.random_range(spec.speed.clone());
-
-                let velocity_x: f32 = speed * angle.cos();
-                let velocity_y: f32 = speed * angle.sin();
-
-                engine.particle_state.push(ParticleState {
-                    pos: (x, y),
-                    velocity: (velocity_x, velocity_y),
-                    color: spec.color.clone(),
-                    gravity_scale: spec.gravity_scale,
-                    spawn_timestamp: engine.game_time,
-                    death_timestamp: engine.game_time + spec.lifetime_sec,
-                    layer_index,
-                })
-            }
-        }
-        ParticleEmitterShape::Cone {
-            direction_deg,
-            width_deg,
-        } => {
-            for _ in 0..emitter.count {
-                let half_angle_rad: f32 = (width_deg / 2.0).to_radians();
-                let direction_rad: f32 = direction_deg.to_radians();
+    let mut ctx = SpawnContext {
+        particle_state: &mut engine.particle_state,
+        rng: &mut *engine.particle_rng,
+        game_time: engine.game_time,
+        aspect_ratio: engine.cell_aspect_ratio,
+        budget: engine.particle_budget,
+    };
+    emit_particles(
+        &mut ctx,
+        SpawnOrigin {
+            layer_index,
+            pos: (x, y),
+            velocity: (0.0, 0.0),
+        },
+        spec,
+        emitter,
+        emitter.count,
+    );
+}
+
+/// Starts a continuous emitter, which keeps spawning particles until it's stopped or runs out of time.
+///
+/// The emitter is owned by the engine and updated at the end of every frame.
+/// The returned [`EmitterHandle`] can be used to move, pause, resume or stop the emitter.
+///
+/// Spawning timing is controlled by [`ContinuousEmitter`], while [`ParticleEmitter::count`]
+/// determines the size of each burst.
+///
+/// # Example
+/// ```rust,no_run
+/// # use germterm::{layer::create_layer, engine::Engine, particle::{start_emitter, ContinuousEmitter, ParticleSpec, ParticleEmitter}};
+/// let mut engine = Engine::new(40, 20);
+/// let layer = create_layer(&mut engine, 0);
+///
+/// let smoke = start_emitter(
+///     &mut engine,
+///     layer,
+///     20.0,
+///     18.0,
+///     ParticleSpec::default(),
+///     ParticleEmitter::default(),
+///     ContinuousEmitter {
+///         rate: 40.0,
+///         ..Default::default()
+///     },
+/// );
+/// ```
+pub fn start_emitter(
+    engine: &mut Engine,
+    layer_index: LayerIndex,
+    x: f32,
+    y: f32,
+    spec: ParticleSpec,
+    emitter: ParticleEmitter,
+    continuous: ContinuousEmitter,
+) -> EmitterHandle {
+    let handle = EmitterHandle(engine.next_emitter_id);
+    engine.next_emitter_id += 1;
+
+    engine.particle_emitters.push(EmitterState {
+        handle,
+        layer_index,
+        pos: (x, y),
+        spec,
+        emitter,
+        continuous,
+        paused: false,
+        elapsed_sec: 0.0,
+        spawn_accumulator: 0.0,
+        next_burst_sec: 0.0,
+    });
+
+    handle
+}
+
+/// Moves a continuous emitter to a new position.
+///
+/// Particles that were already spawned are not affected.
+pub fn move_emitter(engine: &mut Engine, handle: EmitterHandle, x: f32, y: f32) {
+    if let Some(state) = find_emitter_mut(engine, handle) {
+        state.pos = (x, y);
+    }
+}
+
+/// Pauses a continuous emitter.
+///
+/// A paused emitter does not spawn particles and its duration does not run out.
+pub fn pause_emitter(engine: &mut Engine, handle: EmitterHandle) {
+    if let Some(state) = find_emitter_mut(engine, handle) {
+        state.paused = true;
+    }
+}
+
+/// Resumes a continuous emitter previously paused with [`pause_emitter`].
+pub fn resume_emitter(engine: &mut Engine, handle: EmitterHandle) {
+    if let Some(state) = find_emitter_mut(engine, handle) {
+        state.paused = false;
+    }
+}
+
+/// Stops and removes a continuous emitter.
+///
+/// Particles that were already spawned keep living until the end of their lifetime.
+pub fn stop_emitter(engine: &mut Engine, handle: EmitterHandle) {
+    engine
+        .particle_emitters
+        .retain(|state| state.handle != handle);
+}
+
+/// Returns `true` if the emitter has neither been stopped nor ran out of its duration.
+pub fn is_emitter_alive(engine: &Engine, handle: EmitterHandle) -> bool {
+    engine
+        .particle_emitters
+        .iter()
+        .any(|state| state.handle == handle)
+}
+
+fn find_emitter_mut(engine: &mut Engine, handle: EmitterHandle) -> Option<&mut EmitterState> {
+    engine
+        .particle_emitters
+        .iter_mut()
+        .find(|state| state.handle == handle)
+}
+
+/// Where a batch of particles is spawned.
+#[derive(Clone, Copy)]
+struct SpawnOrigin {
+    layer_index: LayerIndex,
+    pos: (f32, f32),
+    /// Added on top of the velocity of every spawned particle.
+    velocity: (f32, f32),
+}
+
+/// Engine state needed to spawn particles.
+struct SpawnContext<'a> {
+    particle_state: &'a mut Vec<ParticleState>,
+    rng: &'a mut dyn RngCore,
+    game_time: f32,
+    aspect_ratio: f32,
+    budget: Option<ParticleBudget>,
+}
+
+fn emit_particles(
+    ctx: &mut SpawnContext,
+    origin: SpawnOrigin,
+    spec: &ParticleSpec,
+    emitter: &ParticleEmitter,
+    count: usize,
+) {
+    let curves: Option<Arc<ParticleCurves>> =
+        (!spec.curves.is_empty()).then(|| Arc::new(spec.curves.clone()));
+    let mut rng: &mut dyn RngCore = &mut *ctx.rng;
+    let game_time: f32 = ctx.game_time;
+
+    let count: usize = match ctx.budget {
+        Some(ParticleBudget {
+            max_particles,
+            eviction: ParticleEvictionPolicy::RefuseNew,
+        }) => count.min(max_particles.saturating_sub(ctx.particle_state.len())),
+        Some(ParticleBudget { max_particles, .. }) => count.min(max_particles),
+        None => count,
+    };
+
+    for _ in 0..count {
+        let (offset, normal_rad) = sample_shape(&emitter.shape, ctx.aspect_ratio, &mut rng);
+
+        let angle: f32 = match (&emitter.shape, emitter.direction, normal_rad) {
+            (
+                ParticleEmitterShape::Cone {
+                    direction_deg,
+                    width_deg,
+                },
+                _,
+                _,
+            ) => random_angle_within(*direction_deg, *width_deg, &mut rng),
+            (
+                _,
+                EmitterDirection::Fixed {
+                    direction_deg,
+                    spread_deg,
+                },
+                _,
+            ) => random_angle_within(direction_deg, spread_deg, &mut rng),
+            (_, EmitterDirection::Normal, Some(normal_rad)) => normal_rad,
+            _ if offset != (0.0, 0.0) => (offset.1 / ctx.aspect_ratio).atan2(offset.0),
+            _ => rng.random_range(0.0..=2.0 * PI),
+        };
 
-                let random_angle_offset: f32 = rng.random_range(-half_angle_rad..half_angle_rad);
-                let particle_angle: f32 = direction_rad + random_angle_offset;
+        let speed: f32 = sample_range(&spec.speed, &mut rng);
+        let velocity_x: f32 = origin.velocity.0 + speed * angle.cos();
+        let velocity_y: f32 = origin.velocity.1 + speed * angle.sin();
+        let lifetime_sec: f32 = sample_range(&spec.lifetime_sec, &mut rng);
 
-                let speed: f32 = rng.random_range(spec.speed.clone());
-                let velocity_x: f32 = speed * particle_angle.cos();
-                let velocity_y: f32 = speed * particle_angle.sin();
+        ctx.particle_state.push(ParticleState {
+            pos: (origin.pos.0 + offset.0, origin.pos.1 + offset.1),
+            prev_pos: (origin.pos.0 + offset.0, origin.pos.1 + offset.1),
+            velocity: (velocity_x, velocity_y),
+            color: pick_particle_color(&spec.color, &mut rng),
+            render: spec.render.clone(),
+            curves: curves.clone(),
+            gravity_scale: sample_range(&spec.gravity_scale, &mut rng),
+            gravity: spec.gravity,
+            drag: sample_range(&spec.drag, &mut rng),
+            collision: spec.collision,
+            collide_with: spec.collide_with,
+            stuck: false,
+            trail: spec.trail,
+            trail_positions: match spec.trail {
+                Some(trail) => VecDeque::with_capacity(trail.length + 2),
+                None => VecDeque::new(),
+            },
+            on_death: spec.on_death.clone(),
+            on_collision: spec.on_collision.clone(),
+            event_tag: spec.event_tag,
+            spawn_timestamp: game_time,
+            death_timestamp: game_time + lifetime_sec,
+            layer_index: origin.layer_index,
+        })
+    }
+
+    if let Some(budget) = ctx.budget {
+        evict_oldest_particles(ctx.particle_state, budget.max_particles);
+    }
+}
+
+/// Removes the oldest particles until at most `max_particles` are left.
+fn evict_oldest_particles(particle_state: &mut Vec<ParticleState>, max_particles: usize) {
+    if particle_state.len() <= max_particles {
+        return;
+    }
+
+    // Moves the newest `max_particles` particles to the front
+    particle_state.select_nth_unstable_by(max_particles, |a, b| {
+        b.spawn_timestamp.total_cmp(&a.spawn_timestamp)
+    });
+    particle_state.truncate(max_particles);
+}
+
+/// Samples a random value within `range`, swapping reversed bounds instead of panicking.
+///
+/// Ranges with a non-finite bound return their lower bound.
+fn sample_range(range: &RangeInclusive<f32>, rng: &mut impl Rng) -> f32 {
+    let (start, end) = (*range.start(), *range.end());
+    let (low, high) = if start <= end {
+        (start, end)
+    } else {
+        (end, start)
+    };
+    if !low.is_finite() || !high.is_finite() {
+        return low;
+    }
+    rng.random_range(low..=high)
+}
+
+/// Random angle in radians within `width_deg` around `direction_deg`.
+fn random_angle_within(direction_deg: f32, width_deg: f32, rng: &mut impl Rng) -> f32 {
+    let half_angle_rad: f32 = (width_deg / 2.0).abs().to_radians();
+    direction_deg.to_radians() + rng.random_range(-half_angle_rad..=half_angle_rad)
+}
+
+/// Picks a random spawn point within `shape`.
+///
+/// Returns the offset of the point from the emitter position and the outward normal
+/// of the shape at that point in radians, if the shape has one.
+fn sample_shape(
+    shape: &ParticleEmitterShape,
+    aspect_ratio: f32,
+    rng: &mut impl Rng,
+) -> ((f32, f32), Option<f32>) {
+    const RIGHT: f32 = 0.0;
+    const DOWN: f32 = PI / 2.0;
+    const LEFT: f32 = PI;
+    const UP: f32 = -PI / 2.0;
 
-                engine.particle_state.push(ParticleState {
-                    pos: (x, y),
-                    velocity: (velocity_x, velocity_y),
-                    color: spec.color.clone(),
-                    gravity_scale: spec.gravity_scale,
-                    spawn_timestamp: engine.game_time,
-                    death_timestamp: engine.game_time + spec.lifetime_sec,
-                    layer_index,
-                })
+    match shape {
+        ParticleEmitterShape::Circle | ParticleEmitterShape::Cone { .. } => ((0.0, 0.0), None),
+        ParticleEmitterShape::Rect {
+            width,
+            height,
+            edge_only: false,
+        } => {
+            let (half_width, half_height): (f32, f32) = (width.abs() / 2.0, height.abs() / 2.0);
+            let x: f32 = rng.random_range(-half_width..=half_width);
+            let y: f32 = rng.random_range(-half_height..=half_height);
+
+            // Normal of the closest edge
+            let normal: f32 = if half_width - x.abs() < half_height - y.abs() {
+                if x < 0.0 { LEFT } else { RIGHT }
+            } else if y < 0.0 {
+                UP
+            } else {
+                DOWN
+            };
+            ((x, y), Some(normal))
+        }
+        ParticleEmitterShape::Rect {
+            width,
+            height,
+            edge_only: true,
+        } => {
+            let (width, height): (f32, f32) = (width.abs(), height.abs());
+            let (half_width, half_height): (f32, f32) = (width / 2.0, height / 2.0);
+            let perimeter: f32 = 2.0 * (width + height);
+            if perimeter <= 0.0 {
+                return ((0.0, 0.0), None);
+            }
+
+            // Walk clockwise along the outline, starting at the top left corner
+            let d: f32 = rng.random_range(0.0..perimeter);
+            if d < width {
+                ((d - half_width, -half_height), Some(UP))
+            } else if d < width + height {
+                ((half_width, d - width - half_height), Some(RIGHT))
+            } else if d < 2.0 * width + height {
+                ((half_width - (d - width - height), half_height), Some(DOWN))
+            } else {
+                (
+                    (-half_width, half_height - (d - 2.0 * width - height)),
+                    Some(LEFT),
+                )
+            }
+        }
+        ParticleEmitterShape::Line { dx, dy } => {
+            let t: f32 = rng.random_range(0.0..=1.0);
+            let line_angle: f32 = (dy / aspect_ratio).atan2(*dx);
+            let side: f32 = if rng.random_bool(0.5) { 1.0 } else { -1.0 };
+            ((dx * t, dy * t), Some(line_angle + side * PI / 2.0))
+        }
+        ParticleEmitterShape::Ring {
+            inner_radius,
+            outer_radius,
+        } => {
+            let (inner, outer): (f32, f32) = (
+                inner_radius.abs().min(outer_radius.abs()),
+                inner_radius.abs().max(outer_radius.abs()),
+            );
+            let angle: f32 = rng.random_range(0.0..=2.0 * PI);
+            // Uniform over the area of the ring rather than its radius
+            let radius: f32 = rng.random_range(inner * inner..=outer * outer).sqrt();
+            (
+                (radius * angle.cos(), radius * angle.sin() * aspect_ratio),
+                Some(angle),
+            )
+        }
+        ParticleEmitterShape::Points(points) => {
+            if points.is_empty() {
+                return ((0.0, 0.0), None);
             }
+            (points[rng.random_range(0..points.len())], None)
         }
     }
 }
 
-/// Tiny debug helper that displays the alive particle count.
-#[inline]
-pub fn particle_count(engine: &Engine) -> usize {
-    engine.particle_state.len()
+/// Resolves a [`ParticleColor`] into the color of a single particle,
+/// picking a random entry from weighted sets.
+fn pick_particle_color(color: &ParticleColor, rng: &mut impl Rng) -> ParticleStateColor {
+    match color {
+        ParticleColor::Solid(color) => ParticleStateColor::Solid(*color),
+        ParticleColor::Gradient(gradient) => ParticleStateColor::Gradient(gradient.clone()),
+        ParticleColor::WeightedSolid(colors) => {
+            ParticleStateColor::Solid(pick_weighted(colors, rng).copied().unwrap_or(Color::WHITE))
+        }
+        ParticleColor::WeightedGradient(gradients) => match pick_weighted(gradients, rng) {
+            Some(gradient) => ParticleStateColor::Gradient(gradient.clone()),
+            None => ParticleStateColor::Solid(Color::WHITE),
+        },
+    }
 }
 
-pub(crate) fn update_and_draw_particles(engine: &mut Engine) {
-    let gravity: f32 = 200.0;
-    let drag: f32 = 3.0;
-    let drag_decay: f32 = 1.0 / (1.0 + drag * engine.delta_time);
-    // y:x aspect ratio to account for terminal cells not being perfect squares
-    // and not making the end result look stretched out vertically
-    let aspect_ratio: f32 = 1.0 / 2.0;
+/// Picks a random item from a weighted set.
+///
+/// Returns `None` if no item has a positive weight, or the weights add up to infinity.
+fn pick_weighted<'a, T>(items: &'a [(T, f32)], rng: &mut impl Rng) -> Option<&'a T> {
+    let total_weight: f32 = items.iter().map(|(_, weight)| weight.max(0.0)).sum();
+    if total_weight <= 0.0 || !total_weight.is_finite() {
+        return None;
+    }
 
-    let mut i: usize = 0;
-    while i < engine.particle_state.len() {
-        let (layer_index, x, y, color) = {
-            let state: &mut ParticleState = &mut engine.particle_state[i];
+    let mut remaining: f32 = rng.random_range(0.0..total_weight);
+    let mut last_picked: Option<&T> = None;
+    for (item, weight) in items {
+        let weight: f32 = weight.max(0.0);
+        if weight <= 0.0 {
+            continue;
+        }
+        if remaining < weight {
+            return Some(item);
+        }
+        remaining -= weight;
+        last_picked = Some(item);
+    }
 
-            if engine.game_time >= state.death_timestamp {
-                engine.particle_state.swap_remove(i);
-                continue;
+    // Rounding errors can leave a tiny remainder
+    last_picked
+}
+
+fn update_emitters(engine: &mut Engine, delta_time: f32) {
+    let mut ctx = SpawnContext {
+        particle_state: &mut engine.particle_state,
+        rng: &mut *engine.particle_rng,
+        game_time: engine.game_time,
+        aspect_ratio: engine.cell_aspect_ratio,
+        budget: engine.particle_budget,
+    };
+
+    engine.particle_emitters.retain_mut(|state| {
+        if state.paused {
+            return true;
+        }
+
+        state.elapsed_sec += delta_time;
+        let expired: bool = state
+            .continuous
+            .duration_sec
+            .is_some_and(|duration_sec| state.elapsed_sec > duration_sec);
+        if expired {
+            return false;
+        }
+
+        state.spawn_accumulator += state.continuous.rate.max(0.0) * delta_time;
+        let mut count: usize = state.spawn_accumulator as usize;
+        state.spawn_accumulator -= count as f32;
+
+        if let Some(interval_sec) = state.continuous.burst_interval_sec {
+            // Bursts fire as soon as the emitter starts, then every `interval_sec`
+            while state.next_burst_sec <= state.elapsed_sec {
+                count += state.emitter.count;
+                state.next_burst_sec += interval_sec.max(f32::EPSILON);
             }
+        }
 
-            let t: f32 = ((engine.game_time - state.spawn_timestamp)
-                / (state.death_timestamp - state.spawn_timestamp))
-                .clamp(0.0, 1.0);
+        emit_particles(
+            &mut ctx,
+            SpawnOrigin {
+                layer_index: state.layer_index,
+                pos: state.pos,
+                velocity: (0.0, 0.0),
+            },
+            &state.spec,
+            &state.emitter,
+            count,
+        );
 
-            let color: Color = match &state.color {
-                ParticleColor::Solid(color) => *color,
-                ParticleColor::Gradient(color_gradient) => sample_gradient(color_gradient, t),
-            };
+        true
+    });
+}
+
+/// Sets the world gravity vector applied to particles.
+///
+/// Particles with [`ParticleSpec::gravity`] set use their own gravity vector instead.
+/// Either way, the vector is multiplied by [`ParticleSpec::gravity_scale`].
+///
+/// Defaults to `(0.0, 200.0)`, pointing down.
+pub fn set_particle_gravity(engine: &mut Engine, x: f32, y: f32) {
+    engine.particle_gravity = (x, y);
+}
+
+/// Sets the y:x aspect ratio of terminal cells, used by the particle physics.
+///
+/// This keeps particle motion from looking stretched out vertically, since terminal cells are not perfect squares.
+///
+/// Defaults to `0.5`, which looks good on most terminals.
+pub fn set_cell_aspect_ratio(engine: &mut Engine, value: f32) {
+    engine.cell_aspect_ratio = value;
+}
+
+/// Adds a force field affecting every alive particle.
+///
+/// # Example
+/// ```rust,no_run
+/// # use germterm::{engine::Engine, particle::{add_force_field, ForceField}};
+/// let mut engine = Engine::new(40, 20);
+///
+/// let wind = add_force_field(&mut engine, ForceField::Wind { x: 30.0, y: 0.0 });
+/// let black_hole = add_force_field(
+///     &mut engine,
+///     ForceField::Attractor {
+///         x: 20.0,
+///         y: 10.0,
+///         strength: 400.0,
+///         radius: 15.0,
+///     },
+/// );
+/// ```
+pub fn add_force_field(engine: &mut Engine, force_field: ForceField) -> ForceFieldHandle {
+    let handle = ForceFieldHandle(engine.next_force_field_id);
+    engine.next_force_field_id += 1;
+    engine.force_fields.push((handle, force_field));
+    handle
+}
 
-            state.velocity.1 += gravity * state.gravity_scale * engine.delta_time;
+/// Replaces an existing force field, eg. to move an attractor or change the wind direction.
+pub fn update_force_field(engine: &mut Engine, handle: ForceFieldHandle, force_field: ForceField) {
+    if let Some((_, existing)) = engine.force_fields.iter_mut().find(|(h, _)| *h == handle) {
+        *existing = force_field;
+    }
+}
 
-            state.velocity.0 *= drag_decay;
-            state.velocity.1 *= drag_decay;
+/// Removes a force field.
+pub fn remove_force_field(engine: &mut Engine, handle: ForceFieldHandle) {
+    engine.force_fields.retain(|(h, _)| *h != handle);
+}
 
-            state.pos.0 += state.velocity.0 * engine.delta_time;
-            state.pos.1 += state.velocity.1 * engine.delta_time * aspect_ratio;
+/// Removes all force fields.
+pub fn clear_force_fields(engine: &mut Engine) {
+    engine.force_fields.clear();
+}
 
-            (state.layer_index, state.pos.0, state.pos.1, color)
-        };
+/// Adds a solid rect particles can collide with.
+///
+/// Only particles with [`CollisionTargets::SOLIDS`] in [`ParticleSpec::collide_with`] and a [`ParticleCollision`]
+/// other than [`ParticleCollision::None`] are affected.
+///
+/// # Example
+/// ```rust,no_run
+/// # use germterm::{engine::Engine, particle::add_solid_rect};
+/// let mut engine = Engine::new(40, 20);
+///
+/// // The floor of the level
+/// let floor = add_solid_rect(&mut engine, 0.0, 18.0, 40.0, 2.0);
+/// ```
+pub fn add_solid_rect(engine: &mut Engine, x: f32, y: f32, width: f32, height: f32) -> SolidHandle {
+    let handle = SolidHandle(engine.next_solid_id);
+    engine.next_solid_id += 1;
+    engine.solid_rects.push((
+        handle,
+        SolidRect {
+            x,
+            y,
+            width,
+            height,
+        },
+    ));
+    handle
+}
 
-        draw_octad(engine, layer_index, x, y, color);
+/// Removes a solid rect.
+pub fn remove_solid_rect(engine: &mut Engine, handle: SolidHandle) {
+    engine.solid_rects.retain(|(h, _)| *h != handle);
+}
 
-        i += 1;
+/// Removes all solid rects.
+pub fn clear_solid_rects(engine: &mut Engine) {
+    engine.solid_rects.clear();
+}
+
+/// Sets the solid mask particles can collide with, replacing the previous one.
+///
+/// Passing `None` removes the mask.
+pub fn set_solid_mask(engine: &mut Engine, mask: Option<SolidMask>) {
+    engine.solid_mask = mask;
+}
+
+/// Reseeds the random number generator used by the particle system.
+///
+/// Seeding makes particle effects reproducible: the same seed, spawn calls and frame timing
+/// always produce identical particles. For fully deterministic results, the frame timing
+/// has to be deterministic as well, eg. by using a fixed timestep.
+///
+/// The generator can also be seeded upfront with [`Engine::particle_seed`].
+///
+/// # Example
+/// ```rust,no_run
+/// # use germterm::{engine::Engine, particle::seed_particle_rng};
+/// let mut engine = Engine::new(40, 20);
+/// seed_particle_rng(&mut engine, 1234);
+/// ```
+pub fn seed_particle_rng(engine: &mut Engine, seed: u64) {
+    engine.particle_rng = Box::new(StdRng::seed_from_u64(seed));
+}
+
+/// Replaces the random number generator used by the particle system with a custom one.
+///
+/// Useful when the output has to match across library versions or platforms,
+/// since the default generator does not guarantee a stable output between versions.
+pub fn set_particle_rng(engine: &mut Engine, rng: impl RngCore + Send + 'static) {
+    engine.particle_rng = Box::new(rng);
+}
+
+pub(crate) fn default_particle_rng() -> Box<dyn RngCore + Send> {
+    Box::new(StdRng::from_os_rng())
+}
+
+/// Deaths and collisions of particles spawned with [`ParticleSpec::event_tag`] set.
+///
+/// Events are collected while particles are updated inside [`end_frame`](crate::engine::end_frame),
+/// so the returned events are the ones that happened during the previous frame.
+///
+/// # Example
+/// ```rust,no_run
+/// # use germterm::{engine::Engine, particle::{particle_events, ParticleEventKind}};
+/// # let engine = Engine::new(40, 20);
+/// const SPARK: u32 = 1;
+///
+/// for event in particle_events(&engine) {
+///     if event.tag == SPARK && event.kind == ParticleEventKind::Collision {
+///         // Play a sound, shake the screen, ...
+///     }
+/// }
+/// ```
+pub fn particle_events(engine: &Engine) -> &[ParticleEvent] {
+    &engine.particle_events
+}
+
+/// Caps the amount of alive particles, replacing the previous budget.
+///
+/// Passing `None` removes the cap. Setting a budget lower than the amount of alive particles
+/// immediately removes the oldest particles, regardless of [`ParticleBudget::eviction`].
+///
+/// # Example
+/// ```rust,no_run
+/// # use germterm::{engine::Engine, particle::{set_particle_budget, ParticleBudget, ParticleEvictionPolicy}};
+/// # let mut engine = Engine::new(40, 20);
+/// set_particle_budget(
+///     &mut engine,
+///     Some(ParticleBudget {
+///         max_particles: 10_000,
+///         eviction: ParticleEvictionPolicy::DropOldest,
+///     }),
+/// );
+/// ```
+pub fn set_particle_budget(engine: &mut Engine, budget: Option<ParticleBudget>) {
+    engine.particle_budget = budget;
+    if let Some(budget) = budget {
+        evict_oldest_particles(&mut engine.particle_state, budget.max_particles);
+    }
+}
+
+/// Removes all alive particles.
+///
+/// Running emitters keep spawning new particles, use [`stop_emitter`] to stop them.
+pub fn clear_particles(engine: &mut Engine) {
+    engine.particle_state.clear();
+}
+
+/// Removes all alive particles on the given layer.
+///
+/// Running emitters keep spawning new particles, use [`stop_emitter`] to stop them.
+pub fn clear_particles_on_layer(engine: &mut Engine, layer_index: LayerIndex) {
+    engine
+        .particle_state
+        .retain(|state| state.layer_index != layer_index);
+}
+
+/// Amount of alive particles on the given layer.
+pub fn particle_count_on_layer(engine: &Engine, layer_index: LayerIndex) -> usize {
+    engine
+        .particle_state
+        .iter()
+        .filter(|state| state.layer_index == layer_index)
+        .count()
+}
+
+/// Tiny debug helper that displays the alive particle count.
+#[inline]
+pub fn particle_count(engine: &Engine) -> usize {
+    engine.particle_state.len()
+}
+
+/// Advances emitters and particles by `delta_time`.
+///
+/// Called once per frame, or once per fixed step when the fixed timestep is enabled.
+pub(crate) fn update_particles(engine: &mut Engine, delta_time: f32) {
+    update_emitters(engine, delta_time);
+
+    let physics = PhysicsContext {
+        world_gravity: engine.particle_gravity,
+        aspect_ratio: engine.cell_aspect_ratio,
+        force_fields: &engine.force_fields,
+        frame_size: (engine.frame.width as f32, engine.frame.height as f32),
+        solid_rects: &engine.solid_rects,
+        solid_mask: engine.solid_mask.as_ref(),
+        game_time: engine.game_time,
+        delta_time,
+    };
+
+    let events: &mut Vec<ParticleEvent> = &mut engine.particle_events;
+    let pending: &mut Vec<PendingSubEmitter> = &mut engine.pending_sub_emitters;
+
+    let mut i: usize = 0;
+    while i < engine.particle_state.len() {
+        let state: &mut ParticleState = &mut engine.particle_state[i];
+
+        let (alive, collided) = if physics.game_time >= state.death_timestamp {
+            (false, false)
+        } else {
+            simulate_particle(state, &physics)
+        };
+
+        // Particles killed by a collision only report the collision
+        if collided {
+            on_particle_event(state, ParticleEventKind::Collision, events, pending);
+        } else if !alive {
+            on_particle_event(state, ParticleEventKind::Death, events, pending);
+        }
+        if !alive {
+            engine.particle_state.swap_remove(i);
+            continue;
+        }
+
+        i += 1;
+    }
+
+    let mut ctx = SpawnContext {
+        particle_state: &mut engine.particle_state,
+        rng: &mut *engine.particle_rng,
+        game_time: engine.game_time,
+        aspect_ratio: engine.cell_aspect_ratio,
+        budget: engine.particle_budget,
+    };
+    for PendingSubEmitter {
+        sub_emitter,
+        origin,
+    } in engine.pending_sub_emitters.drain(..)
+    {
+        emit_particles(
+            &mut ctx,
+            origin,
+            &sub_emitter.spec,
+            &sub_emitter.emitter,
+            sub_emitter.emitter.count,
+        );
+    }
+}
+
+/// Draws all alive particles.
+///
+/// `alpha` interpolates between the previous and the current position of each particle.
+pub(crate) fn draw_particles(engine: &mut Engine, alpha: f32) {
+    for i in 0..engine.particle_state.len() {
+        let state: &ParticleState = &engine.particle_state[i];
+
+        let t: f32 = lifetime_progress(state, engine.game_time);
+        let (color, size): (Color, f32) = particle_appearance(state, t);
+
+        let x: f32 = interpolate(state.prev_pos.0, state.pos.0, alpha);
+        let y: f32 = interpolate(state.prev_pos.1, state.pos.1, alpha);
+        let layer_index: LayerIndex = state.layer_index;
+        let render: ParticleRender = state.render.clone();
+
+        // Trails are drawn oldest first, so that the particle itself ends up on top
+        if let Some(trail) = state.trail {
+            let trail_len: usize = state.trail_positions.len().min(trail.length);
+            for k in (0..trail_len).rev() {
+                let pos: (f32, f32) = trail_position(&engine.particle_state[i], k, alpha);
+                let fade: f32 = trail.start_alpha * (1.0 - k as f32 / trail.length as f32);
+                let trail_alpha: f32 = color.a() as f32 * fade;
+                let trail_color: Color =
+                    color.with_alpha(trail_alpha.round().clamp(0.0, 255.0) as u8);
+                draw_particle(engine, layer_index, &render, pos, trail_color, t, size);
+            }
+        }
+
+        draw_particle(engine, layer_index, &render, (x, y), color, t, size);
+    }
+}
+
+/// Color and size of a particle at the normalized lifetime `t`, after applying its curves.
+fn particle_appearance(state: &ParticleState, t: f32) -> (Color, f32) {
+    let mut color: Color = match &state.color {
+        ParticleStateColor::Solid(color) => *color,
+        ParticleStateColor::Gradient(color_gradient) => sample_gradient(color_gradient, t),
+    };
+    let mut size: f32 = 1.0;
+
+    if let Some(curves) = &state.curves {
+        if let Some(alpha_curve) = &curves.alpha {
+            let alpha: f32 = color.a() as f32 * sample_curve(alpha_curve, t);
+            color = color.with_alpha(alpha.round().clamp(0.0, 255.0) as u8);
+        }
+        if let Some(size_curve) = &curves.size {
+            size = sample_curve(size_curve, t);
+        }
+    }
+
+    (color, size)
+}
+
+/// Position of the `k`th trail sample behind a particle, interpolated by `alpha` like the particle itself,
+/// so that the trail stays a whole number of steps behind the drawn particle.
+fn trail_position(state: &ParticleState, k: usize, alpha: f32) -> (f32, f32) {
+    let newer: (f32, f32) = state.trail_positions[k];
+    let older: (f32, f32) = state.trail_positions.get(k + 1).copied().unwrap_or(newer);
+    (
+        interpolate(older.0, newer.0, alpha),
+        interpolate(older.1, newer.1, alpha),
+    )
+}
+
+/// Normalized lifetime of a particle, going from `0.0` at spawn to `1.0` at death.
+#[inline]
+fn lifetime_progress(state: &ParticleState, game_time: f32) -> f32 {
+    ((game_time - state.spawn_timestamp) / (state.death_timestamp - state.spawn_timestamp))
+        .clamp(0.0, 1.0)
+}
+
+/// Draws a single particle at `(x, y)` using the given [`ParticleRender`].
+///
+/// `t` is the normalized lifetime of the particle.
+/// `size` is the diameter of the drawn cluster in units of the render format.
+fn draw_particle(
+    engine: &mut Engine,
+    layer_index: LayerIndex,
+    render: &ParticleRender,
+    (x, y): (f32, f32),
+    color: Color,
+    t: f32,
+    size: f32,
+) {
+    let diameter: usize = size.round().max(0.0) as usize;
+    if diameter == 1 || matches!(render, ParticleRender::Sprite(_)) {
+        draw_particle_unit(engine, layer_index, render, x, y, color, t);
+        return;
+    }
+
+    // Distance between neighbouring units of the render format, in cells
+    let (step_x, step_y): (f32, f32) = match render {
+        ParticleRender::Octad | ParticleRender::Blocktad => (0.5, 0.25),
+        ParticleRender::Twoxel => (1.0, 0.5),
+        ParticleRender::HorizontalTwoxel => (0.5, 1.0),
+        _ => (1.0, 1.0),
+    };
+    let radius: f32 = diameter as f32 / 2.0;
+    let center_offset: f32 = (diameter as f32 - 1.0) / 2.0;
+
+    for row in 0..diameter {
+        let offset_y: f32 = row as f32 - center_offset;
+        for col in 0..diameter {
+            let offset_x: f32 = col as f32 - center_offset;
+            if offset_x * offset_x + offset_y * offset_y > radius * radius {
+                continue;
+            }
+
+            let unit_x: f32 = x + offset_x * step_x;
+            let unit_y: f32 = y + offset_y * step_y;
+            draw_particle_unit(engine, layer_index, render, unit_x, unit_y, color, t);
+        }
+    }
+}
+
+/// Draws a single unit of a particle's render format at `x`, `y`.
+fn draw_particle_unit(
+    engine: &mut Engine,
+    layer_index: LayerIndex,
+    render: &ParticleRender,
+    x: f32,
+    y: f32,
+    color: Color,
+    t: f32,
+) {
+    match render {
+        ParticleRender::Octad => draw_octad(engine, layer_index, x, y, color),
+        ParticleRender::Blocktad => draw_blocktad(engine, layer_index, x, y, color),
+        ParticleRender::Twoxel => draw_twoxel(engine, layer_index, x, y, color),
+        ParticleRender::HorizontalTwoxel => {
+            draw_horizontal_twoxel(engine, layer_index, x, y, color)
+        }
+        ParticleRender::Char(ch) => {
+            let rich_text: RichText = RichText::new(ch.to_string()).with_fg(color);
+            draw_text(
+                engine,
+                layer_index,
+                x.floor() as i16,
+                y.floor() as i16,
+                rich_text,
+            );
+        }
+        ParticleRender::CharSequence(chars) => {
+            if chars.is_empty() {
+                return;
+            }
+            let index: usize = ((t * chars.len() as f32) as usize).min(chars.len() - 1);
+            let rich_text: RichText = RichText::new(chars[index].to_string()).with_fg(color);
+            draw_text(
+                engine,
+                layer_index,
+                x.floor() as i16,
+                y.floor() as i16,
+                rich_text,
+            );
+        }
+        ParticleRender::Sprite(rows) => {
+            let width: usize = rows
+                .iter()
+                .map(|row| row.chars().count())
+                .max()
+                .unwrap_or(0);
+            let left: i16 = (x - width as f32 / 2.0).round() as i16;
+            let top: i16 = (y - rows.len() as f32 / 2.0).round() as i16;
+
+            for (row_index, row) in rows.iter().enumerate() {
+                let rich_text: RichText = RichText::new(row.as_str()).with_fg(color);
+                draw_text(engine, layer_index, left, top + row_index as i16, rich_text);
+            }
+        }
+    }
+}
+
+/// Engine state shared by all particles during a single simulation step.
+struct PhysicsContext<'a> {
+    world_gravity: (f32, f32),
+    // y:x aspect ratio to account for terminal cells not being perfect squares
+    // and not making the end result look stretched out vertically
+    aspect_ratio: f32,
+    force_fields: &'a [(ForceFieldHandle, ForceField)],
+    frame_size: (f32, f32),
+    solid_rects: &'a [(SolidHandle, SolidRect)],
+    solid_mask: Option<&'a SolidMask>,
+    game_time: f32,
+    delta_time: f32,
+}
+
+/// Records the event of a particle and queues up its sub-emitter.
+fn on_particle_event(
+    state: &ParticleState,
+    kind: ParticleEventKind,
+    events: &mut Vec<ParticleEvent>,
+    pending: &mut Vec<PendingSubEmitter>,
+) {
+    if let Some(tag) = state.event_tag {
+        events.push(ParticleEvent {
+            kind,
+            tag,
+            x: state.pos.0,
+            y: state.pos.1,
+            velocity: state.velocity,
+            layer_index: state.layer_index,
+        });
+    }
+
+    let sub_emitter: Option<&Arc<SubEmitter>> = match kind {
+        ParticleEventKind::Death => state.on_death.as_ref(),
+        ParticleEventKind::Collision => state.on_collision.as_ref(),
+    };
+
+    if let Some(sub_emitter) = sub_emitter {
+        let inherit: f32 = sub_emitter.inherit_velocity;
+        pending.push(PendingSubEmitter {
+            sub_emitter: sub_emitter.clone(),
+            origin: SpawnOrigin {
+                layer_index: state.layer_index,
+                pos: state.pos,
+                velocity: (state.velocity.0 * inherit, state.velocity.1 * inherit),
+            },
+        });
+    }
+}
+
+/// Advances a single particle by one simulation step.
+///
+/// Returns whether the particle is still alive and whether it collided during the step.
+fn simulate_particle(state: &mut ParticleState, physics: &PhysicsContext) -> (bool, bool) {
+    state.prev_pos = state.pos;
+
+    // One position more than drawn, to interpolate the oldest drawn position from
+    if let Some(trail) = state.trail {
+        state.trail_positions.push_front(state.pos);
+        state.trail_positions.truncate(trail.length + 1);
+    }
+
+    if state.stuck {
+        return (true, false);
+    }
+
+    let delta_time: f32 = physics.delta_time;
+    let t: f32 = lifetime_progress(state, physics.game_time);
+    let (speed_mult, gravity_mult) = match &state.curves {
+        Some(curves) => (
+            curves
+                .speed
+                .as_ref()
+                .map_or(1.0, |curve| sample_curve(curve, t)),
+            curves
+                .gravity
+                .as_ref()
+                .map_or(1.0, |curve| sample_curve(curve, t)),
+        ),
+        None => (1.0, 1.0),
+    };
+
+    let gravity: (f32, f32) = state.gravity.unwrap_or(physics.world_gravity);
+    let field_force: (f32, f32) = sum_force_fields(
+        physics.force_fields,
+        state.pos,
+        physics.aspect_ratio,
+        physics.game_time,
+    );
+
+    let gravity_scale: f32 = state.gravity_scale * gravity_mult;
+    let acceleration: (f32, f32) = (
+        gravity.0 * gravity_scale + field_force.0,
+        gravity.1 * gravity_scale + field_force.1,
+    );
+    state.velocity.0 += acceleration.0 * delta_time;
+    state.velocity.1 += acceleration.1 * delta_time;
+
+    let drag_decay: f32 = 1.0 / (1.0 + state.drag * delta_time);
+    state.velocity.0 *= drag_decay;
+    state.velocity.1 *= drag_decay;
+
+    let prev_pos: (f32, f32) = state.pos;
+    state.pos.0 += state.velocity.0 * delta_time * speed_mult;
+    state.pos.1 += state.velocity.1 * delta_time * speed_mult * physics.aspect_ratio;
+
+    if state.collision == ParticleCollision::None {
+        return (true, false);
+    }
+
+    let (hit_x, hit_y) = detect_collision(
+        prev_pos,
+        state.pos,
+        state.collide_with,
+        physics.frame_size,
+        physics.solid_rects,
+        physics.solid_mask,
+    );
+    if !hit_x && !hit_y {
+        return (true, false);
+    }
+
+    match state.collision {
+        ParticleCollision::None => {}
+        ParticleCollision::Kill => return (false, true),
+        ParticleCollision::Bounce { restitution } => {
+            // A particle resting against a solid only picks up the speed of a single step,
+            // so it comes to rest instead of bouncing and colliding on every step
+            let mut bounced: bool = false;
+            if hit_x {
+                state.pos.0 = prev_pos.0;
+                bounced |= bounce_axis(
+                    &mut state.velocity.0,
+                    acceleration.0,
+                    restitution,
+                    delta_time,
+                );
+            }
+            if hit_y {
+                state.pos.1 = prev_pos.1;
+                bounced |= bounce_axis(
+                    &mut state.velocity.1,
+                    acceleration.1,
+                    restitution,
+                    delta_time,
+                );
+            }
+            return (true, bounced);
+        }
+        ParticleCollision::Stick => {
+            state.pos = prev_pos;
+            state.velocity = (0.0, 0.0);
+            state.stuck = true;
+        }
+    }
+
+    (true, true)
+}
+
+/// Speed in cells per second below which a bouncing particle comes to rest.
+const MIN_BOUNCE_SPEED: f32 = 0.5;
+
+/// Reflects `velocity` along an axis a particle collided on.
+///
+/// Returns whether the particle bounced off, or `false` if it came to rest
+/// because it hit too slowly to bounce.
+fn bounce_axis(velocity: &mut f32, acceleration: f32, restitution: f32, delta_time: f32) -> bool {
+    let rebound: f32 = -*velocity * restitution;
+    // Twice the speed gained during the step, so that even elastic particles come to rest
+    if rebound.abs() <= MIN_BOUNCE_SPEED.max(2.0 * acceleration.abs() * delta_time) {
+        *velocity = 0.0;
+        return false;
+    }
+    *velocity = rebound;
+    true
+}
+
+/// Sums the acceleration of all force fields acting on a particle at `pos`.
+///
+/// Distances are measured with the y axis scaled by `aspect_ratio`,
+/// so that radii appear circular on screen.
+fn sum_force_fields(
+    force_fields: &[(ForceFieldHandle, ForceField)],
+    pos: (f32, f32),
+    aspect_ratio: f32,
+    game_time: f32,
+) -> (f32, f32) {
+    let mut force: (f32, f32) = (0.0, 0.0);
+
+    for (_, force_field) in force_fields {
+        match *force_field {
+            ForceField::Wind { x, y } => {
+                force.0 += x;
+                force.1 += y;
+            }
+            ForceField::Attractor {
+                x,
+                y,
+                strength,
+                radius,
+            } => {
+                if let Some((dir_x, dir_y, falloff)) =
+                    direction_with_falloff(pos, (x, y), radius, aspect_ratio)
+                {
+                    force.0 += dir_x * strength * falloff;
+                    force.1 += dir_y * strength * falloff;
+                }
+            }
+            ForceField::Vortex {
+                x,
+                y,
+                strength,
+                radius,
+            } => {
+                if let Some((dir_x, dir_y, falloff)) =
+                    direction_with_falloff(pos, (x, y), radius, aspect_ratio)
+                {
+                    // Tangent of the direction towards the center, clockwise on screen
+                    force.0 += dir_y * strength * falloff;
+                    force.1 += -dir_x * strength * falloff;
+                }
+            }
+            ForceField::Turbulence {
+                strength,
+                scale,
+                speed,
+            } => {
+                let angle: f32 = turbulence_angle(pos.0 * scale, pos.1 * scale, game_time * speed);
+                force.0 += angle.cos() * strength;
+                force.1 += angle.sin() * strength;
+            }
+        }
+    }
+
+    force
+}
+
+/// Normalized direction from `pos` towards `center` and a linear falloff factor,
+/// or `None` if `pos` is outside of `radius`.
+#[inline]
+fn direction_with_falloff(
+    pos: (f32, f32),
+    center: (f32, f32),
+    radius: f32,
+    aspect_ratio: f32,
+) -> Option<(f32, f32, f32)> {
+    let dx: f32 = center.0 - pos.0;
+    let dy: f32 = (center.1 - pos.1) / aspect_ratio;
+    let distance: f32 = (dx * dx + dy * dy).sqrt();
+
+    if distance >= radius || distance <= f32::EPSILON {
+        return None;
+    }
+
+    Some((dx / distance, dy / distance, 1.0 - distance / radius))
+}
+
+/// Cheap smooth noise used by [`ForceField::Turbulence`], returning an angle in radians.
+#[inline]
+fn turbulence_angle(x: f32, y: f32, t: f32) -> f32 {
+    let n: f32 = (x * 1.7 + t).sin() * (y * 2.3 - t * 0.7).cos()
+        + (x * 0.6 - y * 1.1 + t * 1.3).sin() * 0.5
+        + (y * 3.1 + x * 0.4 - t * 0.5).cos() * 0.25;
+
+    n * PI
+}
+
+/// Checks whether a particle moving from `prev_pos` to `pos` collided with anything in `collide_with`.
+///
+/// Returns which axes the collision happened along, so the particle can be bounced off correctly.
+fn detect_collision(
+    prev_pos: (f32, f32),
+    pos: (f32, f32),
+    collide_with: CollisionTargets,
+    (frame_width, frame_height): (f32, f32),
+    solid_rects: &[(SolidHandle, SolidRect)],
+    solid_mask: Option<&SolidMask>,
+) -> (bool, bool) {
+    let mut hit_x: bool = false;
+    let mut hit_y: bool = false;
+
+    // Only movement towards the outside of the screen or into a solid counts,
+    // so particles that already are outside can move back in
+    if collide_with.contains(CollisionTargets::SCREEN_BOUNDS) {
+        hit_x |=
+            (pos.0 < 0.0 && pos.0 < prev_pos.0) || (pos.0 >= frame_width && pos.0 > prev_pos.0);
+        hit_y |=
+            (pos.1 < 0.0 && pos.1 < prev_pos.1) || (pos.1 >= frame_height && pos.1 > prev_pos.1);
+    }
+
+    if collide_with.contains(CollisionTargets::SOLIDS) {
+        let is_solid = |(x, y): (f32, f32)| -> bool {
+            let in_rect: bool = solid_rects.iter().any(|(_, rect)| {
+                x >= rect.x && x < rect.x + rect.width && y >= rect.y && y < rect.y + rect.height
+            });
+            let in_mask: bool = solid_mask
+                .is_some_and(|mask| x >= 0.0 && y >= 0.0 && mask.is_solid(x as u16, y as u16));
+            in_rect || in_mask
+        };
+
+        if is_solid(pos) && !is_solid(prev_pos) {
+            let solid_x: bool = is_solid((pos.0, prev_pos.1));
+            let solid_y: bool = is_solid((prev_pos.0, pos.1));
+
+            if solid_x || solid_y {
+                hit_x |= solid_x;
+                hit_y |= solid_y;
+            } else {
+                // Diagonal hit on a corner
+                hit_x = true;
+                hit_y = true;
+            }
+        }
+    }
+
+    (hit_x, hit_y)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{cell::CellFormat, easing::Easing, frame::DrawCall};
+
+    fn emitter_test_engine(continuous: ContinuousEmitter) -> (Engine, EmitterHandle) {
+        let mut engine = Engine::new(40, 20);
+        let layer = crate::layer::create_layer(&mut engine, 0);
+        let emitter = ParticleEmitter {
+            count: 5,
+            ..Default::default()
+        };
+        let handle = start_emitter(
+            &mut engine,
+            layer,
+            20.0,
+            10.0,
+            ParticleSpec {
+                lifetime_sec: 100.0..=100.0,
+                ..Default::default()
+            },
+            emitter,
+            continuous,
+        );
+        (engine, handle)
+    }
+
+    #[test]
+    fn emitter_rate_accumulates_fractional_particles() {
+        let (mut engine, _) = emitter_test_engine(ContinuousEmitter {
+            rate: 10.0,
+            ..Default::default()
+        });
+        for _ in 0..8 {
+            update_emitters(&mut engine, 0.125);
+        }
+        assert_eq!(engine.particle_state.len(), 10);
+    }
+
+    #[test]
+    fn emitter_bursts_start_immediately_then_repeat() {
+        let (mut engine, _) = emitter_test_engine(ContinuousEmitter {
+            rate: 0.0,
+            burst_interval_sec: Some(1.0),
+            duration_sec: None,
+        });
+        update_emitters(&mut engine, 0.1);
+        assert_eq!(engine.particle_state.len(), 5);
+        update_emitters(&mut engine, 0.5);
+        assert_eq!(engine.particle_state.len(), 5);
+        update_emitters(&mut engine, 0.5);
+        assert_eq!(engine.particle_state.len(), 10);
+    }
+
+    #[test]
+    fn emitter_expires_and_pauses() {
+        let (mut engine, handle) = emitter_test_engine(ContinuousEmitter {
+            rate: 10.0,
+            burst_interval_sec: None,
+            duration_sec: Some(1.0),
+        });
+        pause_emitter(&mut engine, handle);
+        update_emitters(&mut engine, 5.0);
+        assert!(is_emitter_alive(&engine, handle));
+        assert_eq!(engine.particle_state.len(), 0);
+
+        resume_emitter(&mut engine, handle);
+        update_emitters(&mut engine, 0.5);
+        assert_eq!(engine.particle_state.len(), 5);
+        update_emitters(&mut engine, 0.6);
+        assert!(!is_emitter_alive(&engine, handle));
+    }
+
+    #[test]
+    fn killing_collisions_only_report_the_collision() {
+        let mut engine = Engine::new(40, 20);
+        let layer = crate::layer::create_layer(&mut engine, 0);
+        let sub_emitter = Arc::new(SubEmitter {
+            spec: ParticleSpec::default(),
+            emitter: ParticleEmitter {
+                count: 3,
+                ..Default::default()
+            },
+            inherit_velocity: 0.0,
+        });
+        let spec = ParticleSpec {
+            speed: 100.0..=100.0,
+            gravity_scale: 0.0..=0.0,
+            collision: ParticleCollision::Kill,
+            on_death: Some(sub_emitter.clone()),
+            on_collision: Some(sub_emitter),
+            event_tag: Some(1),
+            ..Default::default()
+        };
+        let emitter = ParticleEmitter {
+            direction: EmitterDirection::Fixed {
+                direction_deg: 180.0,
+                spread_deg: 0.0,
+            },
+            count: 1,
+            ..Default::default()
+        };
+        spawn_particles(&mut engine, layer, 1.0, 10.0, &spec, &emitter);
+
+        update_particles(&mut engine, 0.1);
+
+        let kinds: Vec<ParticleEventKind> = particle_events(&engine)
+            .iter()
+            .map(|event| event.kind)
+            .collect();
+        assert_eq!(kinds, [ParticleEventKind::Collision]);
+        assert_eq!(engine.particle_state.len(), 3);
+    }
+
+    /// Spawns a single particle with an event tag, moving at `speed` along `direction_deg`.
+    fn spawn_test_particle(
+        engine: &mut Engine,
+        (x, y): (f32, f32),
+        speed: f32,
+        direction_deg: f32,
+        spec: ParticleSpec,
+    ) {
+        let layer = crate::layer::create_layer(engine, 0);
+        let spec = ParticleSpec {
+            speed: speed..=speed,
+            lifetime_sec: 100.0..=100.0,
+            event_tag: Some(1),
+            ..spec
+        };
+        let emitter = ParticleEmitter {
+            direction: EmitterDirection::Fixed {
+                direction_deg,
+                spread_deg: 0.0,
+            },
+            count: 1,
+            ..Default::default()
+        };
+        spawn_particles(engine, layer, x, y, &spec, &emitter);
+    }
+
+    /// Simulates `steps` steps of 1/60 s, returning how many collisions were reported.
+    fn step_particles(engine: &mut Engine, steps: usize) -> usize {
+        let mut collisions: usize = 0;
+        for _ in 0..steps {
+            engine.particle_events.clear();
+            update_particles(engine, 1.0 / 60.0);
+            engine.game_time += 1.0 / 60.0;
+            collisions += particle_events(engine)
+                .iter()
+                .filter(|event| event.kind == ParticleEventKind::Collision)
+                .count();
+        }
+        collisions
+    }
+
+    #[test]
+    fn kill_collisions_remove_the_particle() {
+        let mut engine = Engine::new(40, 20);
+        let spec = ParticleSpec {
+            collision: ParticleCollision::Kill,
+            ..Default::default()
+        };
+        spawn_test_particle(&mut engine, (20.0, 10.0), 0.0, 0.0, spec);
+
+        assert_eq!(step_particles(&mut engine, 120), 1);
+        assert!(engine.particle_state.is_empty());
+    }
+
+    #[test]
+    fn bouncing_particles_come_to_rest_on_the_floor() {
+        let mut engine = Engine::new(40, 20);
+        let spec = ParticleSpec {
+            collision: ParticleCollision::Bounce { restitution: 0.5 },
+            ..Default::default()
+        };
+        spawn_test_particle(&mut engine, (20.0, 2.0), 0.0, 0.0, spec);
+
+        let bounces: usize = step_particles(&mut engine, 300);
+        assert!((1..10).contains(&bounces), "{bounces} bounces");
+
+        // Resting on the floor doesn't collide anymore
+        assert_eq!(step_particles(&mut engine, 120), 0);
+        let state: &ParticleState = &engine.particle_state[0];
+        assert!(state.pos.1 > 19.0 && state.pos.1 < 20.0);
+        assert_eq!(state.velocity.1, 0.0);
+    }
+
+    #[test]
+    fn bouncing_reflects_the_velocity_along_the_hit_axis() {
+        let mut engine = Engine::new(40, 20);
+        set_particle_gravity(&mut engine, 0.0, 0.0);
+        let spec = ParticleSpec {
+            collision: ParticleCollision::Bounce { restitution: 1.0 },
+            drag: 0.0..=0.0,
+            ..Default::default()
+        };
+        spawn_test_particle(&mut engine, (2.0, 10.0), 60.0, 180.0, spec);
+
+        assert_eq!(step_particles(&mut engine, 10), 1);
+        let state: &ParticleState = &engine.particle_state[0];
+        assert!((state.velocity.0 - 60.0).abs() < 1e-3);
+        assert!(state.pos.0 >= 0.0);
+    }
+
+    #[test]
+    fn sticking_particles_stay_in_place() {
+        let mut engine = Engine::new(40, 20);
+        set_particle_gravity(&mut engine, 0.0, 0.0);
+        add_solid_rect(&mut engine, 25.0, 0.0, 5.0, 20.0);
+        let spec = ParticleSpec {
+            collision: ParticleCollision::Stick,
+            ..Default::default()
+        };
+        spawn_test_particle(&mut engine, (20.0, 10.0), 60.0, 0.0, spec);
+
+        assert_eq!(step_particles(&mut engine, 10), 1);
+        let pos: (f32, f32) = engine.particle_state[0].pos;
+        assert!(pos.0 < 25.0);
+
+        assert_eq!(step_particles(&mut engine, 60), 0);
+        assert_eq!(engine.particle_state[0].pos, pos);
+        assert!(engine.particle_state[0].stuck);
+    }
+
+    #[test]
+    fn trails_stay_whole_steps_behind_the_interpolated_particle() {
+        let mut engine = Engine::new(40, 20);
+        set_particle_gravity(&mut engine, 0.0, 0.0);
+        let spec = ParticleSpec {
+            drag: 0.0..=0.0,
+            trail: Some(ParticleTrail {
+                length: 3,
+                start_alpha: 1.0,
+            }),
+            ..Default::default()
+        };
+        // One cell per step to the right
+        spawn_test_particle(&mut engine, (10.0, 10.0), 60.0, 0.0, spec);
+        step_particles(&mut engine, 5);
+
+        let state: &ParticleState = &engine.particle_state[0];
+        assert_eq!(state.trail_positions.len(), 4);
+        for alpha in [0.0, 0.25, 0.5, 1.0] {
+            let x: f32 = interpolate(state.prev_pos.0, state.pos.0, alpha);
+            for k in 0..3 {
+                let (trail_x, trail_y) = trail_position(state, k, alpha);
+                assert!((x - trail_x - (k + 1) as f32).abs() < 1e-3);
+                assert!((trail_y - 10.0).abs() < 1e-3);
+            }
+        }
+    }
+
+    #[test]
+    fn alpha_and_size_follow_their_curves() {
+        let mut engine = Engine::new(40, 20);
+        let alpha_curve = Curve::Keyframes(Arc::new(vec![(0.0, 0.0), (0.2, 1.0), (1.0, 0.0)]));
+        let size_curve = Curve::Eased {
+            from: 1.0,
+            to: 5.0,
+            easing: Easing::QuadIn,
+        };
+        let spec = ParticleSpec {
+            color: ParticleColor::Solid(Color::new(255, 0, 0, 200)),
+            curves: ParticleCurves {
+                alpha: Some(alpha_curve.clone()),
+                size: Some(size_curve.clone()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        spawn_test_particle(&mut engine, (20.0, 10.0), 0.0, 0.0, spec);
+
+        let state: &ParticleState = &engine.particle_state[0];
+        for t in [0.0, 0.1, 0.2, 0.5, 0.9, 1.0] {
+            let (color, size) = particle_appearance(state, t);
+            let alpha: f32 = 200.0 * sample_curve(&alpha_curve, t);
+            assert_eq!(color.a(), alpha.round() as u8, "alpha at {t}");
+            assert_eq!(size, sample_curve(&size_curve, t), "size at {t}");
+        }
+    }
+
+    #[test]
+    fn particles_without_curves_keep_their_color_and_size() {
+        let mut engine = Engine::new(40, 20);
+        let color = Color::new(0, 255, 0, 120);
+        let spec = ParticleSpec {
+            color: ParticleColor::Solid(color),
+            ..Default::default()
+        };
+        spawn_test_particle(&mut engine, (20.0, 10.0), 0.0, 0.0, spec);
+
+        for t in [0.0, 0.5, 1.0] {
+            let (particle_color, size) = particle_appearance(&engine.particle_state[0], t);
+            assert_eq!(particle_color, color);
+            assert_eq!(size, 1.0);
+        }
+    }
+
+    #[test]
+    fn speed_and_gravity_curves_scale_the_motion() {
+        let mut engine = Engine::new(40, 20);
+        let spec = ParticleSpec {
+            drag: 0.0..=0.0,
+            curves: ParticleCurves {
+                speed: Some(Curve::Constant(0.0)),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        spawn_test_particle(&mut engine, (20.0, 10.0), 60.0, 0.0, spec);
+        let spec = ParticleSpec {
+            drag: 0.0..=0.0,
+            curves: ParticleCurves {
+                gravity: Some(Curve::Constant(0.0)),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        spawn_test_particle(&mut engine, (20.0, 10.0), 60.0, 0.0, spec);
+
+        step_particles(&mut engine, 6);
+        assert_eq!(engine.particle_state[0].pos, (20.0, 10.0));
+        let (x, y) = engine.particle_state[1].pos;
+        assert!((x - 26.0).abs() < 1e-3);
+        assert!((y - 10.0).abs() < 1e-3);
+    }
+
+    /// Draw calls of a single resting particle drawn with `render`.
+    fn draw_test_particle(render: ParticleRender) -> Vec<DrawCall> {
+        let mut engine = Engine::new(40, 20).headless(true);
+        let spec = ParticleSpec {
+            render,
+            color: ParticleColor::Solid(Color::RED),
+            ..Default::default()
+        };
+        spawn_test_particle(&mut engine, (20.0, 10.0), 0.0, 0.0, spec);
+        crate::engine::init(&mut engine).unwrap();
+        draw_particles(&mut engine, 1.0);
+        std::mem::take(&mut engine.frame.layered_draw_queue[0].0)
+    }
+
+    #[test]
+    fn render_formats_draw_their_cell_format() {
+        for (render, format) in [
+            (ParticleRender::Octad, CellFormat::Octad),
+            (ParticleRender::Blocktad, CellFormat::Blocktad),
+            (ParticleRender::Twoxel, CellFormat::Twoxel),
+            (
+                ParticleRender::HorizontalTwoxel,
+                CellFormat::HorizontalTwoxel,
+            ),
+            (ParticleRender::Char('*'), CellFormat::Standard),
+        ] {
+            let draws: Vec<DrawCall> = draw_test_particle(render);
+            assert_eq!(draws.len(), 1);
+            assert!(draws[0].rich_text.cell_format == format);
+            assert_eq!((draws[0].x, draws[0].y), (20, 10));
+            assert_eq!(draws[0].rich_text.fg, Color::RED);
+            assert_ne!(draws[0].rich_text.text.trim(), "");
+        }
+    }
+
+    #[test]
+    fn character_renders_draw_their_characters() {
+        let draws: Vec<DrawCall> = draw_test_particle(ParticleRender::Char('*'));
+        assert_eq!(draws[0].rich_text.text.as_str(), "*");
+
+        // Freshly spawned particles show the first character of the sequence
+        let sequence = ParticleRender::CharSequence(Arc::new(vec!['+', 'x', '.']));
+        let draws: Vec<DrawCall> = draw_test_particle(sequence);
+        assert_eq!(draws[0].rich_text.text.as_str(), "+");
+
+        // Sprites are centered on the particle
+        let sprite = ParticleRender::Sprite(Arc::new(vec!["()".to_string(), "/\\".to_string()]));
+        let draws: Vec<DrawCall> = draw_test_particle(sprite);
+        assert_eq!(draws.len(), 2);
+        assert!(
+            draws
+                .iter()
+                .all(|draw| draw.rich_text.cell_format == CellFormat::Standard)
+        );
+        assert_eq!((draws[0].x, draws[0].y), (19, 9));
+        assert_eq!((draws[1].x, draws[1].y), (19, 10));
+    }
+
+    /// Positions and solid colors of all alive particles after running a seeded emitter for a second.
+    fn seeded_particles(seed: u64) -> Vec<((f32, f32), Option<Color>)> {
+        let (mut engine, _) = emitter_test_engine(ContinuousEmitter {
+            rate: 50.0,
+            burst_interval_sec: Some(0.5),
+            duration_sec: None,
+        });
+        seed_particle_rng(&mut engine, seed);
+        if let Some(emitter) = engine.particle_emitters.first_mut() {
+            emitter.spec.color = ParticleColor::WeightedSolid(vec![
+                (Color::RED, 1.0),
+                (Color::GREEN, 2.0),
+                (Color::BLUE, 3.0),
+            ]);
+        }
+        step_particles(&mut engine, 60);
+
+        engine
+            .particle_state
+            .iter()
+            .map(|state| {
+                let color: Option<Color> = match state.color {
+                    ParticleStateColor::Solid(color) => Some(color),
+                    ParticleStateColor::Gradient(_) => None,
+                };
+                (state.pos, color)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn seeded_particles_are_reproducible() {
+        let particles = seeded_particles(1234);
+        assert!(particles.len() > 50);
+        assert_eq!(particles, seeded_particles(1234));
+        assert_ne!(particles, seeded_particles(4321));
+    }
+
+    #[test]
+    fn custom_particle_rngs_are_used() {
+        let mut a = Engine::new(40, 20);
+        let mut b = Engine::new(40, 20);
+        set_particle_rng(&mut a, StdRng::seed_from_u64(99));
+        seed_particle_rng(&mut b, 99);
+        let layer = crate::layer::create_layer(&mut a, 0);
+        crate::layer::create_layer(&mut b, 0);
+        for engine in [&mut a, &mut b] {
+            spawn_particles(
+                engine,
+                layer,
+                20.0,
+                10.0,
+                &ParticleSpec::default(),
+                &ParticleEmitter::default(),
+            );
+        }
+        let velocities = |engine: &Engine| -> Vec<(f32, f32)> {
+            engine
+                .particle_state
+                .iter()
+                .map(|state| state.velocity)
+                .collect()
+        };
+        assert_eq!(velocities(&a), velocities(&b));
+    }
+
+    /// Spawns `count` particles on `layer` at the current game time.
+    fn spawn_count(engine: &mut Engine, layer: LayerIndex, count: usize) {
+        let spec = ParticleSpec {
+            lifetime_sec: 100.0..=100.0,
+            ..Default::default()
+        };
+        let emitter = ParticleEmitter {
+            count,
+            ..Default::default()
+        };
+        spawn_particles(engine, layer, 20.0, 10.0, &spec, &emitter);
+    }
+
+    #[test]
+    fn budgets_drop_the_oldest_particles() {
+        let mut engine = Engine::new(40, 20);
+        let layer = crate::layer::create_layer(&mut engine, 0);
+        set_particle_budget(
+            &mut engine,
+            Some(ParticleBudget {
+                max_particles: 10,
+                eviction: ParticleEvictionPolicy::DropOldest,
+            }),
+        );
+
+        for second in 0..3 {
+            engine.game_time = second as f32;
+            spawn_count(&mut engine, layer, 5);
+        }
+        assert_eq!(particle_count(&engine), 10);
+        assert!(
+            engine
+                .particle_state
+                .iter()
+                .all(|state| state.spawn_timestamp >= 1.0)
+        );
+
+        // A single burst larger than the budget keeps only as many particles as fit
+        engine.game_time = 3.0;
+        spawn_count(&mut engine, layer, 25);
+        assert_eq!(particle_count(&engine), 10);
+        assert!(
+            engine
+                .particle_state
+                .iter()
+                .all(|state| state.spawn_timestamp == 3.0)
+        );
+    }
+
+    #[test]
+    fn budgets_can_refuse_new_particles() {
+        let mut engine = Engine::new(40, 20);
+        let layer = crate::layer::create_layer(&mut engine, 0);
+        set_particle_budget(
+            &mut engine,
+            Some(ParticleBudget {
+                max_particles: 8,
+                eviction: ParticleEvictionPolicy::RefuseNew,
+            }),
+        );
+
+        spawn_count(&mut engine, layer, 5);
+        engine.game_time = 1.0;
+        spawn_count(&mut engine, layer, 5);
+        assert_eq!(particle_count(&engine), 8);
+        assert_eq!(
+            engine
+                .particle_state
+                .iter()
+                .filter(|state| state.spawn_timestamp == 0.0)
+                .count(),
+            5
+        );
+    }
+
+    #[test]
+    fn lowering_the_budget_evicts_immediately() {
+        let mut engine = Engine::new(40, 20);
+        let layer = crate::layer::create_layer(&mut engine, 0);
+        spawn_count(&mut engine, layer, 5);
+        engine.game_time = 1.0;
+        spawn_count(&mut engine, layer, 5);
+
+        set_particle_budget(
+            &mut engine,
+            Some(ParticleBudget {
+                max_particles: 3,
+                eviction: ParticleEvictionPolicy::RefuseNew,
+            }),
+        );
+        assert_eq!(particle_count(&engine), 3);
+        assert!(
+            engine
+                .particle_state
+                .iter()
+                .all(|state| state.spawn_timestamp == 1.0)
+        );
+
+        set_particle_budget(&mut engine, None);
+        spawn_count(&mut engine, layer, 20);
+        assert_eq!(particle_count(&engine), 23);
+    }
+
+    #[test]
+    fn particles_are_counted_and_cleared_per_layer() {
+        let mut engine = Engine::new(40, 20);
+        let background = crate::layer::create_layer(&mut engine, 0);
+        let foreground = crate::layer::create_layer(&mut engine, 1);
+        spawn_count(&mut engine, background, 4);
+        spawn_count(&mut engine, foreground, 7);
+
+        assert_eq!(particle_count_on_layer(&engine, background), 4);
+        assert_eq!(particle_count_on_layer(&engine, foreground), 7);
+        assert_eq!(particle_count(&engine), 11);
+
+        clear_particles_on_layer(&mut engine, foreground);
+        assert_eq!(particle_count_on_layer(&engine, background), 4);
+        assert_eq!(particle_count_on_layer(&engine, foreground), 0);
+
+        clear_particles(&mut engine);
+        assert_eq!(particle_count(&engine), 0);
+    }
+
+    /// The force of a single field on a particle at `pos`, with square cells.
+    fn force_at(force_field: ForceField, pos: (f32, f32)) -> (f32, f32) {
+        sum_force_fields(&[(ForceFieldHandle(0), force_field)], pos, 1.0, 0.0)
+    }
+
+    fn assert_force(actual: (f32, f32), expected: (f32, f32)) {
+        assert!(
+            (actual.0 - expected.0).abs() < 1e-3 && (actual.1 - expected.1).abs() < 1e-3,
+            "{actual:?} is not close to {expected:?}"
+        );
+    }
+
+    #[test]
+    fn wind_pushes_everywhere() {
+        let wind = ForceField::Wind { x: 3.0, y: -1.0 };
+        assert_force(force_at(wind.clone(), (0.0, 0.0)), (3.0, -1.0));
+        assert_force(force_at(wind, (100.0, -50.0)), (3.0, -1.0));
+    }
+
+    #[test]
+    fn attractors_pull_towards_their_center_and_fall_off() {
+        let attractor = |strength: f32| ForceField::Attractor {
+            x: 10.0,
+            y: 10.0,
+            strength,
+            radius: 5.0,
+        };
+
+        assert_force(force_at(attractor(100.0), (12.0, 10.0)), (-60.0, 0.0));
+        assert_force(force_at(attractor(100.0), (10.0, 6.0)), (0.0, 20.0));
+        // Repulsors push away
+        assert_force(force_at(attractor(-100.0), (12.0, 10.0)), (60.0, 0.0));
+        // Nothing at and beyond the edge of the radius
+        assert_force(force_at(attractor(100.0), (15.0, 10.0)), (0.0, 0.0));
+        assert_force(force_at(attractor(100.0), (30.0, 10.0)), (0.0, 0.0));
+        // Nor exactly at the center, where there is no direction
+        assert_force(force_at(attractor(100.0), (10.0, 10.0)), (0.0, 0.0));
+    }
+
+    #[test]
+    fn force_field_radii_account_for_the_aspect_ratio() {
+        let attractor = ForceField::Attractor {
+            x: 10.0,
+            y: 10.0,
+            strength: 100.0,
+            radius: 2.0,
+        };
+        // 2 rows below is 1 unit away with cells twice as tall as wide
+        let force = sum_force_fields(&[(ForceFieldHandle(0), attractor)], (10.0, 12.0), 2.0, 0.0);
+        assert_force(force, (0.0, -50.0));
+    }
+
+    #[test]
+    fn vortices_swirl_clockwise_around_their_center() {
+        let vortex = ForceField::Vortex {
+            x: 10.0,
+            y: 10.0,
+            strength: 100.0,
+            radius: 4.0,
+        };
+        // With y pointing down, clockwise goes down on the right and up on the left
+        assert_force(force_at(vortex.clone(), (12.0, 10.0)), (0.0, 50.0));
+        assert_force(force_at(vortex.clone(), (8.0, 10.0)), (0.0, -50.0));
+        assert_force(force_at(vortex.clone(), (10.0, 8.0)), (50.0, 0.0));
+        assert_force(force_at(vortex, (14.0, 10.0)), (0.0, 0.0));
+    }
+
+    #[test]
+    fn turbulence_has_a_constant_strength_and_varies() {
+        let turbulence = ForceField::Turbulence {
+            strength: 5.0,
+            scale: 0.3,
+            speed: 1.0,
+        };
+        let fields = [(ForceFieldHandle(0), turbulence)];
+        let samples: Vec<(f32, f32)> = (0..10)
+            .map(|i| sum_force_fields(&fields, (i as f32 * 3.0, 4.0), 1.0, i as f32 * 0.5))
+            .collect();
+        for (x, y) in &samples {
+            assert!((x.hypot(*y) - 5.0).abs() < 1e-3);
+        }
+        assert!(samples.windows(2).any(|pair| pair[0] != pair[1]));
+        assert_eq!(
+            sum_force_fields(&fields, (3.0, 4.0), 1.0, 2.0),
+            sum_force_fields(&fields, (3.0, 4.0), 1.0, 2.0)
+        );
+    }
+
+    #[test]
+    fn gravity_and_force_fields_accelerate_particles() {
+        let mut engine = Engine::new(40, 20);
+        set_particle_gravity(&mut engine, 6.0, 0.0);
+        add_force_field(&mut engine, ForceField::Wind { x: 0.0, y: -12.0 });
+        let spec = ParticleSpec {
+            gravity_scale: 1.0..=1.0,
+            drag: 0.0..=0.0,
+            ..Default::default()
+        };
+        spawn_test_particle(&mut engine, (20.0, 10.0), 0.0, 0.0, spec);
+
+        step_particles(&mut engine, 30);
+        let velocity: (f32, f32) = engine.particle_state[0].velocity;
+        assert_force(velocity, (3.0, -6.0));
+
+        clear_force_fields(&mut engine);
+        step_particles(&mut engine, 30);
+        assert_force(engine.particle_state[0].velocity, (6.0, -6.0));
+    }
+
+    /// Samples `shape` a thousand times, checking every offset and normal with `check`.
+    fn check_shape_samples(
+        shape: ParticleEmitterShape,
+        aspect_ratio: f32,
+        check: impl Fn((f32, f32), Option<f32>),
+    ) {
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..1000 {
+            let (offset, normal) = sample_shape(&shape, aspect_ratio, &mut rng);
+            check(offset, normal);
+        }
+    }
+
+    fn assert_normal(normal: Option<f32>, expected: f32) {
+        let normal: f32 = normal.expect("shape should have a normal");
+        let diff: f32 = (normal - expected).rem_euclid(2.0 * PI);
+        assert!(
+            diff < 1e-4 || 2.0 * PI - diff < 1e-4,
+            "normal {normal} isn't {expected}"
+        );
+    }
+
+    #[test]
+    fn point_shapes_spawn_at_the_emitter() {
+        for shape in [
+            ParticleEmitterShape::Circle,
+            ParticleEmitterShape::Cone {
+                direction_deg: 90.0,
+                width_deg: 30.0,
+            },
+            ParticleEmitterShape::Points(Arc::new(vec![])),
+        ] {
+            check_shape_samples(shape, 0.5, |offset, normal| {
+                assert_eq!(offset, (0.0, 0.0));
+                assert_eq!(normal, None);
+            });
+        }
+    }
+
+    #[test]
+    fn filled_rects_spawn_inside_and_face_the_closest_edge() {
+        let shape = ParticleEmitterShape::Rect {
+            width: 8.0,
+            height: -4.0,
+            edge_only: false,
+        };
+        check_shape_samples(shape, 0.5, |(x, y), normal| {
+            assert!(x.abs() <= 4.0 && y.abs() <= 2.0, "({x}, {y}) is outside");
+            let expected: f32 = if 4.0 - x.abs() < 2.0 - y.abs() {
+                if x < 0.0 { PI } else { 0.0 }
+            } else if y < 0.0 {
+                -PI / 2.0
+            } else {
+                PI / 2.0
+            };
+            assert_normal(normal, expected);
+        });
+    }
+
+    #[test]
+    fn rect_edges_spawn_on_the_outline() {
+        let shape = ParticleEmitterShape::Rect {
+            width: 8.0,
+            height: 4.0,
+            edge_only: true,
+        };
+        check_shape_samples(shape, 0.5, |(x, y), normal| {
+            let expected: f32 = if (y + 2.0).abs() < 1e-4 {
+                -PI / 2.0
+            } else if (x - 4.0).abs() < 1e-4 {
+                0.0
+            } else if (y - 2.0).abs() < 1e-4 {
+                PI / 2.0
+            } else {
+                assert!((x + 4.0).abs() < 1e-4, "({x}, {y}) isn't on the outline");
+                PI
+            };
+            assert!(x.abs() <= 4.0 + 1e-4 && y.abs() <= 2.0 + 1e-4);
+            assert_normal(normal, expected);
+        });
+    }
+
+    #[test]
+    fn lines_spawn_along_the_segment_facing_either_side() {
+        let shape = ParticleEmitterShape::Line { dx: 10.0, dy: 0.0 };
+        check_shape_samples(shape, 0.5, |(x, y), normal| {
+            assert!(
+                (0.0..=10.0).contains(&x) && y == 0.0,
+                "({x}, {y}) is off the line"
+            );
+            let normal: f32 = normal.expect("lines should have a normal");
+            assert!((normal.abs() - PI / 2.0).abs() < 1e-4);
+        });
+    }
+
+    #[test]
+    fn rings_spawn_between_their_radii() {
+        let shape = ParticleEmitterShape::Ring {
+            inner_radius: 6.0,
+            outer_radius: 3.0,
+        };
+        check_shape_samples(shape, 0.5, |(x, y), normal| {
+            // Vertical offsets are squashed by the aspect ratio
+            let radius: f32 = x.hypot(y / 0.5);
+            assert!(
+                (3.0 - 1e-3..=6.0 + 1e-3).contains(&radius),
+                "radius {radius}"
+            );
+            assert_normal(normal, (y / 0.5).atan2(x));
+        });
+    }
+
+    #[test]
+    fn point_sets_spawn_at_one_of_their_points() {
+        let points: Vec<(f32, f32)> = vec![(1.0, 2.0), (-3.0, 0.5), (0.0, -4.0)];
+        let shape = ParticleEmitterShape::Points(Arc::new(points.clone()));
+        check_shape_samples(shape, 0.5, |offset, normal| {
+            assert!(points.contains(&offset));
+            assert_eq!(normal, None);
+        });
+    }
+
+    #[test]
+    fn reversed_ranges_are_sampled_within_their_bounds() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let range: RangeInclusive<f32> = 40.0..=20.0;
+        for _ in 0..100 {
+            let value: f32 = sample_range(&range, &mut rng);
+            assert!((20.0..=40.0).contains(&value));
+        }
+        assert_eq!(sample_range(&(2.0..=2.0), &mut rng), 2.0);
+    }
+
+    #[test]
+    fn pick_weighted_skips_sets_without_positive_weights() {
+        let mut rng = StdRng::seed_from_u64(0);
+        assert_eq!(pick_weighted::<char>(&[], &mut rng), None);
+        assert_eq!(pick_weighted(&[('a', 0.0), ('b', -1.0)], &mut rng), None);
+        for _ in 0..100 {
+            assert_eq!(
+                pick_weighted(&[('a', 0.0), ('b', 2.0), ('c', -1.0)], &mut rng),
+                Some(&'b')
+            );
+        }
+    }
+
+    #[test]
+    fn empty_weighted_colors_fall_back_to_white() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let color = pick_particle_color(&ParticleColor::WeightedSolid(vec![]), &mut rng);
+        assert!(matches!(color, ParticleStateColor::Solid(Color::WHITE)));
+        let color = pick_particle_color(&ParticleColor::WeightedGradient(vec![]), &mut rng);
+        assert!(matches!(color, ParticleStateColor::Solid(Color::WHITE)));
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn weighted_sets_without_positive_weights_are_rejected() {
+        assert!(serde_json::from_str::<ParticleColor>(r#"{ "WeightedSolid": [] }"#).is_err());
+        assert!(
+            serde_json::from_str::<ParticleColor>(r##"{ "WeightedSolid": [["#ff0000", 0.0]] }"##)
+                .is_err()
+        );
+        assert!(
+            serde_json::from_str::<ParticleColor>(r##"{ "WeightedSolid": [["#ff0000", 1.0]] }"##)
+                .is_ok()
+        );
     }
 }
@@ -0,0 +1,241 @@
+//! Named particle effects.
+//!
+//! A [`ParticleEffect`] bundles a [`ParticleSpec`] with the [`ParticleEmitter`] spawning it,
+//! and a [`ParticleEffectLibrary`] stores effects by name.
+//!
+//! ## Loading from files
+//! With the `serde` feature enabled, effects and libraries can be serialized and deserialized.
+//! The `ron`, `toml` and `json` features add helpers for loading libraries from the respective formats,
+//! which allows tweaking effects without recompiling:
+//!
+//! ```ron
+//! {
+//!     "spark": (
+//!         spec: (
+//!             color: Solid("#ffcc00"),
+//!             speed: (start: 20.0, end: 40.0),
+//!             lifetime_sec: (start: 0.5, end: 1.0),
+//!         ),
+//!         emitter: (count: 40),
+//!     ),
+//! }
+//! ```
+//!
+//! Fields left out fall back to their defaults.
+
+use std::collections::HashMap;
+
+#[cfg(any(feature = "ron", feature = "toml", feature = "json"))]
+use std::{fmt, fs, io, path::Path};
+
+use crate::{
+    engine::Engine,
+    layer::LayerIndex,
+    particle::{ParticleEmitter, ParticleSpec, spawn_particles},
+};
+
+/// A particle spec along with the emitter spawning it.
+#[derive(Clone, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct ParticleEffect {
+    pub spec: ParticleSpec,
+    pub emitter: ParticleEmitter,
+}
+
+/// A collection of [`ParticleEffect`]s stored by name.
+///
+/// Serialized as a map of names to effects.
+#[derive(Clone, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
+pub struct ParticleEffectLibrary {
+    pub effects: HashMap<String, ParticleEffect>,
+}
+
+impl ParticleEffectLibrary {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an effect, replacing the previous effect with the same name.
+    pub fn insert(&mut self, name: impl Into<String>, effect: ParticleEffect) {
+        self.effects.insert(name.into(), effect);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&ParticleEffect> {
+        self.effects.get(name)
+    }
+}
+
+/// Spawns a burst of particles described by a [`ParticleEffect`].
+///
+/// # Example
+/// ```rust,no_run
+/// # use germterm::{engine::Engine, layer::create_layer, particle_effect::{ParticleEffectLibrary, spawn_particle_effect}};
+/// # let mut engine = Engine::new(40, 20);
+/// # let layer = create_layer(&mut engine, 0);
+/// # let library = ParticleEffectLibrary::new();
+/// if let Some(explosion) = library.get("explosion") {
+///     spawn_particle_effect(&mut engine, layer, 20.0, 10.0, explosion);
+/// }
+/// ```
+pub fn spawn_particle_effect(
+    engine: &mut Engine,
+    layer_index: LayerIndex,
+    x: f32,
+    y: f32,
+    effect: &ParticleEffect,
+) {
+    spawn_particles(engine, layer_index, x, y, &effect.spec, &effect.emitter);
+}
+
+/// An error produced when loading a [`ParticleEffectLibrary`].
+#[cfg(any(feature = "ron", feature = "toml", feature = "json"))]
+#[derive(Debug)]
+pub enum ParticleEffectError {
+    Io(io::Error),
+    #[cfg(feature = "ron")]
+    Ron(ron::error::SpannedError),
+    #[cfg(feature = "toml")]
+    Toml(toml::de::Error),
+    #[cfg(feature = "json")]
+    Json(serde_json::Error),
+    /// The file extension doesn't match any enabled format.
+    UnsupportedFormat(String),
+}
+
+#[cfg(any(feature = "ron", feature = "toml", feature = "json"))]
+impl fmt::Display for ParticleEffectError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParticleEffectError::Io(err) => write!(f, "failed to read particle effects: {err}"),
+            #[cfg(feature = "ron")]
+            ParticleEffectError::Ron(err) => write!(f, "invalid RON particle effects: {err}"),
+            #[cfg(feature = "toml")]
+            ParticleEffectError::Toml(err) => write!(f, "invalid TOML particle effects: {err}"),
+            #[cfg(feature = "json")]
+            ParticleEffectError::Json(err) => write!(f, "invalid JSON particle effects: {err}"),
+            ParticleEffectError::UnsupportedFormat(extension) => {
+                write!(f, "unsupported particle effect format `{extension}`")
+            }
+        }
+    }
+}
+
+#[cfg(any(feature = "ron", feature = "toml", feature = "json"))]
+impl std::error::Error for ParticleEffectError {}
+
+/// Parses a [`ParticleEffectLibrary`] from a RON string.
+///
+/// # Example
+/// ```rust
+/// # use germterm::particle_effect::parse_ron_effects;
+/// let library = parse_ron_effects(r##"{ "spark": (emitter: (count: 40)) }"##).unwrap();
+/// assert_eq!(library.get("spark").unwrap().emitter.count, 40);
+/// ```
+#[cfg(feature = "ron")]
+pub fn parse_ron_effects(source: &str) -> Result<ParticleEffectLibrary, ParticleEffectError> {
+    ron::from_str(source).map_err(ParticleEffectError::Ron)
+}
+
+/// Parses a [`ParticleEffectLibrary`] from a TOML string, with one table per effect.
+///
+/// # Example
+/// ```rust
+/// # use germterm::particle_effect::parse_toml_effects;
+/// let library = parse_toml_effects(
+///     r##"
+///     [spark.emitter]
+///     count = 40
+///     "##,
+/// )
+/// .unwrap();
+/// assert_eq!(library.get("spark").unwrap().emitter.count, 40);
+/// ```
+#[cfg(feature = "toml")]
+pub fn parse_toml_effects(source: &str) -> Result<ParticleEffectLibrary, ParticleEffectError> {
+    toml::from_str(source).map_err(ParticleEffectError::Toml)
+}
+
+/// Parses a [`ParticleEffectLibrary`] from a JSON string.
+///
+/// # Example
+/// ```rust
+/// # use germterm::particle_effect::parse_json_effects;
+/// let library = parse_json_effects(r##"{ "spark": { "emitter": { "count": 40 } } }"##).unwrap();
+/// assert_eq!(library.get("spark").unwrap().emitter.count, 40);
+/// ```
+#[cfg(feature = "json")]
+pub fn parse_json_effects(source: &str) -> Result<ParticleEffectLibrary, ParticleEffectError> {
+    serde_json::from_str(source).map_err(ParticleEffectError::Json)
+}
+
+/// Loads a [`ParticleEffectLibrary`] from a file.
+///
+/// The format is picked by the file extension: `.ron`, `.toml` or `.json`,
+/// each of which requires its cargo feature to be enabled.
+///
+/// # Example
+/// ```rust,no_run
+/// # use germterm::particle_effect::load_particle_effects;
+/// let library = load_particle_effects("assets/effects.ron").expect("failed to load effects");
+/// ```
+#[cfg(any(feature = "ron", feature = "toml", feature = "json"))]
+pub fn load_particle_effects(
+    path: impl AsRef<Path>,
+) -> Result<ParticleEffectLibrary, ParticleEffectError> {
+    let path: &Path = path.as_ref();
+    let extension: String = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    let source: String = fs::read_to_string(path).map_err(ParticleEffectError::Io)?;
+
+    match extension.as_str() {
+        #[cfg(feature = "ron")]
+        "ron" => parse_ron_effects(&source),
+        #[cfg(feature = "toml")]
+        "toml" => parse_toml_effects(&source),
+        #[cfg(feature = "json")]
+        "json" => parse_json_effects(&source),
+        _ => Err(ParticleEffectError::UnsupportedFormat(extension)),
+    }
+}
+
+#[cfg(all(test, feature = "json"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reversed_ranges_are_rejected() {
+        for field in ["speed", "lifetime_sec", "gravity_scale", "drag"] {
+            let source: String = format!(
+                r#"{{ "spark": {{ "spec": {{ "{field}": {{ "start": 40, "end": 20 }} }} }} }}"#
+            );
+            assert!(
+                matches!(
+                    parse_json_effects(&source),
+                    Err(ParticleEffectError::Json(_))
+                ),
+                "reversed `{field}` was accepted"
+            );
+        }
+    }
+
+    #[test]
+    fn ordered_ranges_are_accepted() {
+        let library = parse_json_effects(
+            r#"{ "spark": { "spec": { "speed": { "start": 20, "end": 40 }, "drag": { "start": 1, "end": 1 } } } }"#,
+        )
+        .unwrap();
+        assert_eq!(library.get("spark").unwrap().spec.speed, 20.0..=40.0);
+    }
+}
@@ -0,0 +1,451 @@
+//! Timers driven by game time.
+//!
+//! Timers fire after a delay measured in game time, either once or repeatedly.
+//! Like [tweens](crate::tween), they follow [`Engine::game_time`] and are updated during every
+//! [`start_frame`](crate::engine::start_frame), so a timer fires at the start of the first frame its time has passed.
+//! Since game time follows the [time scale](crate::engine::set_time_scale), timers slow down with it
+//! and stand still while the game is paused.
+//!
+//! There are two ways of reacting to a timer:
+//!
+//! - Polling: timers started using [`start_timer`] and [`start_repeating_timer`] are queried using [`timer_fired`].
+//! - Callbacks: [`schedule`] and [`schedule_repeating`] call a closure with the engine when the timer fires.
+//!
+//! Every timer returns a [`TimerHandle`], which can be used to [cancel](cancel_timer), [pause](pause_timer)
+//! and [resume](resume_timer) it.
+//!
+//! # Example
+//! ```rust,no_run
+//! # use germterm::{engine::{Engine, start_frame}, timer::{schedule, start_repeating_timer, timer_fired}};
+//! let mut engine = Engine::new(40, 20);
+//! let move_tick = start_repeating_timer(&mut engine, 0.05);
+//! schedule(&mut engine, 3.0, |engine| {
+//!     // Spawn the first enemy wave
+//! });
+//!
+//! loop {
+//!     start_frame(&mut engine);
+//!     for _ in 0..timer_fired(&engine, move_tick) {
+//!         // Move the snake by a cell
+//!     }
+//! }
+//! ```
+
+use crate::engine::Engine;
+
+/// A handle to a timer owned by the engine.
+///
+/// Returned by [`start_timer`], [`start_repeating_timer`], [`schedule`] and [`schedule_repeating`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TimerHandle(u64);
+
+type TimerCallback = Box<dyn FnMut(&mut Engine) + Send>;
+
+pub(crate) struct Timer {
+    handle: TimerHandle,
+    interval_sec: f32,
+    repeating: bool,
+    // Game time the timer counts from, moved forward by the time spent paused.
+    // Fire times are multiples of the interval rather than accumulated, so they don't drift
+    start_sec: f32,
+    fire_count: u64,
+    // Seconds elapsed when the timer was paused
+    paused_elapsed_sec: Option<f32>,
+    fired: u32,
+    finished: bool,
+    callback: Option<TimerCallback>,
+}
+
+impl Timer {
+    fn next_fire_sec(&self) -> f32 {
+        self.interval_sec * (self.fire_count + 1) as f32
+    }
+
+    fn elapsed_sec(&self, game_time: f32) -> f32 {
+        self.paused_elapsed_sec
+            .unwrap_or(game_time - self.start_sec)
+    }
+}
+
+fn add_timer(
+    engine: &mut Engine,
+    interval_sec: f32,
+    repeating: bool,
+    callback: Option<TimerCallback>,
+) -> TimerHandle {
+    let handle = TimerHandle(engine.next_timer_id);
+    engine.next_timer_id += 1;
+    let start_sec: f32 = engine.game_time;
+    engine.timers.push(Timer {
+        handle,
+        interval_sec: interval_sec.max(0.0),
+        repeating,
+        start_sec,
+        fire_count: 0,
+        paused_elapsed_sec: None,
+        fired: 0,
+        finished: false,
+        callback,
+    });
+    handle
+}
+
+/// Starts a timer firing once after `delay_sec` seconds, queried using [`timer_fired`].
+pub fn start_timer(engine: &mut Engine, delay_sec: f32) -> TimerHandle {
+    add_timer(engine, delay_sec, false, None)
+}
+
+/// Starts a timer firing every `interval_sec` seconds, queried using [`timer_fired`].
+///
+/// An interval of `0.0` fires once every frame.
+pub fn start_repeating_timer(engine: &mut Engine, interval_sec: f32) -> TimerHandle {
+    add_timer(engine, interval_sec, true, None)
+}
+
+/// Calls `callback` once after `delay_sec` seconds.
+pub fn schedule(
+    engine: &mut Engine,
+    delay_sec: f32,
+    callback: impl FnMut(&mut Engine) + Send + 'static,
+) -> TimerHandle {
+    add_timer(engine, delay_sec, false, Some(Box::new(callback)))
+}
+
+/// Calls `callback` every `interval_sec` seconds, until the timer is cancelled.
+///
+/// If several intervals passed during a single frame, `callback` is called once for each of them.
+pub fn schedule_repeating(
+    engine: &mut Engine,
+    interval_sec: f32,
+    callback: impl FnMut(&mut Engine) + Send + 'static,
+) -> TimerHandle {
+    add_timer(engine, interval_sec, true, Some(Box::new(callback)))
+}
+
+/// How many times a timer fired since the previous frame.
+///
+/// A repeating timer can fire several times during a single slow frame.
+pub fn timer_fired(engine: &Engine, handle: TimerHandle) -> u32 {
+    find_timer(engine, handle).map_or(0, |timer| timer.fired)
+}
+
+/// Whether a timer is still waiting to fire, including while paused.
+pub fn is_timer_active(engine: &Engine, handle: TimerHandle) -> bool {
+    find_timer(engine, handle).is_some_and(|timer| !timer.finished)
+}
+
+/// Seconds until a timer fires next, or `None` if it was cancelled or already fired.
+pub fn timer_remaining(engine: &Engine, handle: TimerHandle) -> Option<f32> {
+    let timer: &Timer = find_timer(engine, handle).filter(|timer| !timer.finished)?;
+    Some((timer.next_fire_sec() - timer.elapsed_sec(engine.game_time)).max(0.0))
+}
+
+/// Cancels a timer. It won't fire anymore.
+pub fn cancel_timer(engine: &mut Engine, handle: TimerHandle) {
+    engine.timers.retain(|timer| timer.handle != handle);
+}
+
+/// Cancels all timers.
+pub fn cancel_all_timers(engine: &mut Engine) {
+    engine.timers.clear();
+}
+
+/// Pauses a timer, keeping its remaining time until it's resumed using [`resume_timer`].
+pub fn pause_timer(engine: &mut Engine, handle: TimerHandle) {
+    let game_time: f32 = engine.game_time;
+    if let Some(timer) = find_timer_mut(engine, handle) {
+        timer.paused_elapsed_sec = Some(timer.elapsed_sec(game_time));
+    }
+}
+
+/// Resumes a timer paused using [`pause_timer`].
+pub fn resume_timer(engine: &mut Engine, handle: TimerHandle) {
+    let game_time: f32 = engine.game_time;
+    if let Some(timer) = find_timer_mut(engine, handle)
+        && let Some(elapsed_sec) = timer.paused_elapsed_sec.take()
+    {
+        timer.start_sec = game_time - elapsed_sec;
+    }
+}
+
+/// Whether a timer is paused.
+pub fn is_timer_paused(engine: &Engine, handle: TimerHandle) -> bool {
+    find_timer(engine, handle).is_some_and(|timer| timer.paused_elapsed_sec.is_some())
+}
+
+fn find_timer(engine: &Engine, handle: TimerHandle) -> Option<&Timer> {
+    engine.timers.iter().find(|timer| timer.handle == handle)
+}
+
+fn find_timer_mut(engine: &mut Engine, handle: TimerHandle) -> Option<&mut Timer> {
+    engine
+        .timers
+        .iter_mut()
+        .find(|timer| timer.handle == handle)
+}
+
+/// Fires the timers whose time has passed by [`Engine::game_time`] and calls their callbacks.
+pub(crate) fn update_timers(engine: &mut Engine) {
+    let game_time: f32 = engine.game_time;
+
+    // One-shot timers stay around for the frame they fired in, so that `timer_fired` can see them
+    engine.timers.retain(|timer| !timer.finished);
+
+    let mut due: Vec<(TimerHandle, u32)> = Vec::new();
+    for timer in &mut engine.timers {
+        timer.fired = 0;
+        if timer.paused_elapsed_sec.is_some() {
+            continue;
+        }
+        let elapsed_sec: f32 = timer.elapsed_sec(game_time);
+        if timer.next_fire_sec() > elapsed_sec {
+            continue;
+        }
+
+        if !timer.repeating {
+            timer.fired = 1;
+            timer.finished = true;
+        } else if timer.interval_sec <= 0.0 {
+            timer.fired = 1;
+        } else {
+            let total: u64 = (elapsed_sec / timer.interval_sec) as u64;
+            timer.fired = total.saturating_sub(timer.fire_count).max(1) as u32;
+            timer.fire_count = total.max(timer.fire_count + 1);
+        }
+        if timer.callback.is_some() {
+            due.push((timer.handle, timer.fired));
+        }
+    }
+
+    // Callbacks get the whole engine, so they're taken out while being called
+    for (handle, fired) in due {
+        for _ in 0..fired {
+            let Some(mut callback) = find_timer_mut(engine, handle).and_then(|t| t.callback.take())
+            else {
+                break;
+            };
+            callback(engine);
+            match find_timer_mut(engine, handle) {
+                Some(timer) => timer.callback = Some(callback),
+                // Cancelled by its own callback
+                None => break,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{
+        Arc, Mutex,
+        atomic::{AtomicU32, Ordering},
+    };
+
+    use crate::{
+        easing::Easing,
+        tween::{Tween, create_tweened, is_tween_playing, play_tween, update_tweens},
+    };
+
+    fn advance(engine: &mut Engine, delta_time: f32) {
+        engine.game_time += delta_time;
+        engine.real_time += delta_time;
+        update_tweens(engine);
+        update_timers(engine);
+    }
+
+    #[test]
+    fn timers_and_tweens_share_a_clock() {
+        let mut engine = Engine::new(40, 20);
+        let x = create_tweened(&mut engine, 0.0);
+        let tween = play_tween(&mut engine, Tween::to(x, 1.0, 1.0, Easing::Linear));
+        let timer = start_timer(&mut engine, 1.0);
+
+        for _ in 0..3 {
+            advance(&mut engine, 0.25);
+            assert!(is_tween_playing(&engine, tween));
+            assert_eq!(timer_fired(&engine, timer), 0);
+        }
+        advance(&mut engine, 0.25);
+        assert!(!is_tween_playing(&engine, tween));
+        assert_eq!(timer_fired(&engine, timer), 1);
+    }
+
+    #[test]
+    fn one_shot_timers_fire_once_and_are_removed() {
+        let mut engine = Engine::new(40, 20);
+        let timer = start_timer(&mut engine, 0.5);
+
+        advance(&mut engine, 0.25);
+        assert_eq!(timer_fired(&engine, timer), 0);
+        assert!(is_timer_active(&engine, timer));
+
+        advance(&mut engine, 0.5);
+        assert_eq!(timer_fired(&engine, timer), 1);
+        assert!(!is_timer_active(&engine, timer));
+        assert_eq!(timer_remaining(&engine, timer), None);
+
+        advance(&mut engine, 0.5);
+        assert_eq!(timer_fired(&engine, timer), 0);
+        assert!(engine.timers.is_empty());
+    }
+
+    #[test]
+    fn repeating_timers_catch_up_without_drifting() {
+        let mut engine = Engine::new(40, 20);
+        let timer = start_repeating_timer(&mut engine, 0.25);
+
+        let mut total: u32 = 0;
+        for _ in 0..96 {
+            advance(&mut engine, 0.0625);
+            total += timer_fired(&engine, timer);
+        }
+        assert_eq!(total, 24);
+
+        advance(&mut engine, 0.8);
+        assert_eq!(timer_fired(&engine, timer), 3);
+        assert!(is_timer_active(&engine, timer));
+    }
+
+    #[test]
+    fn zero_interval_timers_fire_every_frame() {
+        let mut engine = Engine::new(40, 20);
+        let timer = start_repeating_timer(&mut engine, 0.0);
+
+        for _ in 0..3 {
+            advance(&mut engine, 0.5);
+            assert_eq!(timer_fired(&engine, timer), 1);
+        }
+    }
+
+    #[test]
+    fn remaining_time_counts_down() {
+        let mut engine = Engine::new(40, 20);
+        let timer = start_repeating_timer(&mut engine, 1.0);
+
+        advance(&mut engine, 0.25);
+        assert!((timer_remaining(&engine, timer).unwrap() - 0.75).abs() < 1e-5);
+        advance(&mut engine, 1.0);
+        assert!((timer_remaining(&engine, timer).unwrap() - 0.75).abs() < 1e-5);
+    }
+
+    #[test]
+    fn paused_timers_keep_their_remaining_time() {
+        let mut engine = Engine::new(40, 20);
+        let timer = start_timer(&mut engine, 1.0);
+
+        advance(&mut engine, 0.5);
+        pause_timer(&mut engine, timer);
+        assert!(is_timer_paused(&engine, timer));
+        for _ in 0..10 {
+            advance(&mut engine, 0.5);
+            assert_eq!(timer_fired(&engine, timer), 0);
+        }
+        assert!(is_timer_active(&engine, timer));
+        assert!((timer_remaining(&engine, timer).unwrap() - 0.5).abs() < 1e-5);
+
+        resume_timer(&mut engine, timer);
+        assert!(!is_timer_paused(&engine, timer));
+        advance(&mut engine, 0.5);
+        assert_eq!(timer_fired(&engine, timer), 1);
+    }
+
+    #[test]
+    fn cancelled_timers_never_fire() {
+        let mut engine = Engine::new(40, 20);
+        let timer = start_timer(&mut engine, 0.5);
+        let other = start_repeating_timer(&mut engine, 0.5);
+
+        cancel_timer(&mut engine, timer);
+        assert!(!is_timer_active(&engine, timer));
+        advance(&mut engine, 1.0);
+        assert_eq!(timer_fired(&engine, timer), 0);
+        assert_eq!(timer_fired(&engine, other), 2);
+
+        cancel_all_timers(&mut engine);
+        assert!(!is_timer_active(&engine, other));
+    }
+
+    #[test]
+    fn callbacks_run_once_per_interval() {
+        let mut engine = Engine::new(40, 20);
+        let calls = Arc::new(AtomicU32::new(0));
+        let repeating_calls = Arc::clone(&calls);
+        schedule_repeating(&mut engine, 0.25, move |_| {
+            repeating_calls.fetch_add(1, Ordering::Relaxed);
+        });
+        let once_calls = Arc::clone(&calls);
+        schedule(&mut engine, 0.5, move |_| {
+            once_calls.fetch_add(100, Ordering::Relaxed);
+        });
+
+        advance(&mut engine, 1.0);
+        assert_eq!(calls.load(Ordering::Relaxed), 104);
+        advance(&mut engine, 1.0);
+        assert_eq!(calls.load(Ordering::Relaxed), 108);
+    }
+
+    #[test]
+    fn callbacks_can_cancel_their_own_timer() {
+        let mut engine = Engine::new(40, 20);
+        let calls = Arc::new(AtomicU32::new(0));
+        let handle = Arc::new(Mutex::new(None::<TimerHandle>));
+
+        let callback_calls = Arc::clone(&calls);
+        let callback_handle = Arc::clone(&handle);
+        let timer = schedule_repeating(&mut engine, 0.1, move |engine| {
+            callback_calls.fetch_add(1, Ordering::Relaxed);
+            if let Some(handle) = *callback_handle.lock().unwrap() {
+                cancel_timer(engine, handle);
+            }
+        });
+        *handle.lock().unwrap() = Some(timer);
+
+        advance(&mut engine, 1.0);
+        assert_eq!(calls.load(Ordering::Relaxed), 1);
+        assert!(!is_timer_active(&engine, timer));
+        advance(&mut engine, 1.0);
+        assert_eq!(calls.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn timers_follow_game_time_under_a_fixed_timestep() {
+        use crate::{
+            engine::{end_frame, init, start_frame},
+            input::recording::{InputRecording, RecordedFrame, start_replay},
+        };
+
+        let mut engine = Engine::new(40, 20).headless(true).fixed_timestep(10);
+        init(&mut engine).unwrap();
+        let frame = RecordedFrame {
+            delta_time: 0.07,
+            ..Default::default()
+        };
+        start_replay(
+            &mut engine,
+            InputRecording {
+                frames: vec![frame; 20],
+                ..Default::default()
+            },
+        );
+        let timer = start_repeating_timer(&mut engine, 0.3);
+
+        let mut fire_times: Vec<f32> = Vec::new();
+        for _ in 0..20 {
+            start_frame(&mut engine);
+            if timer_fired(&engine, timer) > 0 {
+                fire_times.push(engine.game_time);
+            }
+            end_frame(&mut engine).unwrap();
+        }
+
+        // Game time moves in steps of 0.1, so timers fire exactly when it reaches a multiple of the interval
+        assert_eq!(fire_times.len(), 4);
+        for (i, game_time) in fire_times.into_iter().enumerate() {
+            assert!(
+                (game_time - 0.3 * (i + 1) as f32).abs() < 1e-4,
+                "{game_time}"
+            );
+        }
+    }
+}
@@ -0,0 +1,638 @@
+//! Tweening of values over time.
+//!
+//! Tweens animate values owned by the engine, created using [`create_tweened`] and read every frame using [`tweened`].
+//! `f32`, `(f32, f32)` and [`Color`] values can be tweened.
+//!
+//! A [`Tween`] is built from:
+//!
+//! - [`Tween::to`] and [`Tween::from_to`], which animate a single value using an [`Easing`].
+//! - [`Tween::delay`], which waits.
+//! - [`Tween::sequence`], which plays tweens one after another.
+//! - [`Tween::parallel`], which plays tweens at the same time.
+//!
+//! Any tween can be repeated using [`Tween::repeat`] or [`Tween::looped`], and played back and forth using [`Tween::yoyo`].
+//!
+//! Tweens are started using [`play_tween`] and advance by as much as [`Engine::game_time`] did during every
+//! [`start_frame`](crate::engine::start_frame), until they finish or are stopped using [`stop_tween`].
+//! UI animations that should ignore the [time scale](crate::engine::set_time_scale) and keep playing while the game
+//! is paused can follow [`Engine::real_time`] instead using [`Tween::unscaled`].
+//! When a tween ends, its values are left at their final state.
+//!
+//! # Example
+//! ```rust,no_run
+//! # use germterm::{color::Color, easing::Easing, engine::{Engine, start_frame}, tween::{Tween, create_tweened, play_tween, tweened}};
+//! let mut engine = Engine::new(40, 20);
+//! let position = create_tweened(&mut engine, (0.0, 10.0));
+//! let color = create_tweened(&mut engine, Color::WHITE);
+//!
+//! // Slide in while fading to red, then pulse forever
+//! play_tween(
+//!     &mut engine,
+//!     Tween::sequence([
+//!         Tween::parallel([
+//!             Tween::to(position, (20.0, 10.0), 0.8, Easing::BackOut),
+//!             Tween::to(color, Color::RED, 0.8, Easing::Linear),
+//!         ]),
+//!         Tween::to(color, Color::YELLOW, 0.5, Easing::SineInOut)
+//!             .yoyo(true)
+//!             .looped(),
+//!     ]),
+//! );
+//!
+//! loop {
+//!     start_frame(&mut engine);
+//!     let (x, y) = tweened(&engine, position).unwrap();
+//!     // Draw at `x`, `y`...
+//! }
+//! ```
+
+use std::marker::PhantomData;
+
+use crate::{
+    color::{self, Color},
+    easing::{Easing, ease},
+    engine::Engine,
+};
+
+/// A value that can be tweened.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TweenValue {
+    F32(f32),
+    Vec2((f32, f32)),
+    Color(Color),
+}
+
+impl TweenValue {
+    fn lerp(from: TweenValue, to: TweenValue, t: f32) -> TweenValue {
+        match (from, to) {
+            (TweenValue::F32(a), TweenValue::F32(b)) => TweenValue::F32(a + (b - a) * t),
+            (TweenValue::Vec2(a), TweenValue::Vec2(b)) => {
+                TweenValue::Vec2((a.0 + (b.0 - a.0) * t, a.1 + (b.1 - a.1) * t))
+            }
+            (TweenValue::Color(a), TweenValue::Color(b)) => TweenValue::Color(color::lerp(a, b, t)),
+            // Mismatched types can't be built through the typed API
+            _ => to,
+        }
+    }
+}
+
+/// Types that can be tweened, see [`TweenValue`].
+pub trait Tweenable: Copy + Into<TweenValue> {
+    fn from_tween_value(value: TweenValue) -> Option<Self>;
+}
+
+impl From<f32> for TweenValue {
+    fn from(value: f32) -> Self {
+        TweenValue::F32(value)
+    }
+}
+
+impl From<(f32, f32)> for TweenValue {
+    fn from(value: (f32, f32)) -> Self {
+        TweenValue::Vec2(value)
+    }
+}
+
+impl From<Color> for TweenValue {
+    fn from(value: Color) -> Self {
+        TweenValue::Color(value)
+    }
+}
+
+impl Tweenable for f32 {
+    fn from_tween_value(value: TweenValue) -> Option<Self> {
+        match value {
+            TweenValue::F32(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+impl Tweenable for (f32, f32) {
+    fn from_tween_value(value: TweenValue) -> Option<Self> {
+        match value {
+            TweenValue::Vec2(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+impl Tweenable for Color {
+    fn from_tween_value(value: TweenValue) -> Option<Self> {
+        match value {
+            TweenValue::Color(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+/// A handle to a tweened value owned by the engine.
+///
+/// Returned by [`create_tweened`].
+#[derive(Debug, PartialEq, Eq)]
+pub struct Tweened<T>(u64, PhantomData<T>);
+
+impl<T> Clone for Tweened<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Tweened<T> {}
+
+/// A handle to a playing tween.
+///
+/// Returned by [`play_tween`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TweenHandle(u64);
+
+/// An animation of tweened values, see the [module docs](self).
+#[derive(Clone, Debug)]
+pub struct Tween {
+    kind: TweenKind,
+    /// Amount of times to play, `None` for forever.
+    plays: Option<u32>,
+    yoyo: bool,
+    unscaled: bool,
+}
+
+#[derive(Clone, Debug)]
+enum TweenKind {
+    Value {
+        target: u64,
+        // `None` until the tween first starts, then taken from the current value
+        from: Option<TweenValue>,
+        to: TweenValue,
+        duration_sec: f32,
+        easing: Easing,
+        started: bool,
+    },
+    Delay(f32),
+    Sequence(Vec<Tween>),
+    Parallel(Vec<Tween>),
+}
+
+impl Tween {
+    fn new(kind: TweenKind) -> Self {
+        Self {
+            kind,
+            plays: Some(1),
+            yoyo: false,
+            unscaled: false,
+        }
+    }
+
+    /// Animates `target` from the value it has when the tween starts to `to` over `duration_sec` seconds.
+    pub fn to<T: Tweenable>(target: Tweened<T>, to: T, duration_sec: f32, easing: Easing) -> Self {
+        Self::new(TweenKind::Value {
+            target: target.0,
+            from: None,
+            to: to.into(),
+            duration_sec: duration_sec.max(0.0),
+            easing,
+            started: false,
+        })
+    }
+
+    /// Animates `target` from `from` to `to` over `duration_sec` seconds.
+    pub fn from_to<T: Tweenable>(
+        target: Tweened<T>,
+        from: T,
+        to: T,
+        duration_sec: f32,
+        easing: Easing,
+    ) -> Self {
+        Self::new(TweenKind::Value {
+            target: target.0,
+            from: Some(from.into()),
+            to: to.into(),
+            duration_sec: duration_sec.max(0.0),
+            easing,
+            started: false,
+        })
+    }
+
+    /// Waits for `duration_sec` seconds. Mostly useful inside a [`Tween::sequence`].
+    pub fn delay(duration_sec: f32) -> Self {
+        Self::new(TweenKind::Delay(duration_sec.max(0.0)))
+    }
+
+    /// Plays `tweens` one after another.
+    pub fn sequence(tweens: impl IntoIterator<Item = Tween>) -> Self {
+        Self::new(TweenKind::Sequence(tweens.into_iter().collect()))
+    }
+
+    /// Plays `tweens` at the same time, finishing with the longest one.
+    pub fn parallel(tweens: impl IntoIterator<Item = Tween>) -> Self {
+        Self::new(TweenKind::Parallel(tweens.into_iter().collect()))
+    }
+
+    /// Plays the tween `count` times in a row.
+    pub fn repeat(mut self, count: u32) -> Self {
+        self.plays = Some(count.max(1));
+        self
+    }
+
+    /// Plays the tween forever, until stopped using [`stop_tween`].
+    pub fn looped(mut self) -> Self {
+        self.plays = None;
+        self
+    }
+
+    /// Plays every other repetition backwards.
+    pub fn yoyo(mut self, value: bool) -> Self {
+        self.yoyo = value;
+        self
+    }
+
+    /// Advances the tween with [`Engine::real_time`], ignoring the time scale and pausing.
+    ///
+    /// Only has an effect on the tween passed to [`play_tween`].
+    pub fn unscaled(mut self, value: bool) -> Self {
+        self.unscaled = value;
+        self
+    }
+
+    /// Length of a single play in seconds.
+    fn play_duration(&self) -> f32 {
+        match &self.kind {
+            TweenKind::Value { duration_sec, .. } => *duration_sec,
+            TweenKind::Delay(duration_sec) => *duration_sec,
+            TweenKind::Sequence(tweens) => tweens.iter().map(Tween::duration).sum(),
+            TweenKind::Parallel(tweens) => tweens.iter().map(Tween::duration).fold(0.0, f32::max),
+        }
+    }
+
+    /// Length of all plays in seconds, infinite when looped.
+    fn duration(&self) -> f32 {
+        let play_duration: f32 = self.play_duration();
+        match self.plays {
+            _ if play_duration <= 0.0 => 0.0,
+            Some(plays) => play_duration * plays as f32,
+            None => f32::INFINITY,
+        }
+    }
+
+    /// Whether the tween was applied before, so it needs to be rewound when played backwards.
+    fn started(&self) -> bool {
+        match &self.kind {
+            TweenKind::Value { started, .. } => *started,
+            TweenKind::Delay(_) => false,
+            TweenKind::Sequence(tweens) | TweenKind::Parallel(tweens) => {
+                tweens.iter().any(Tween::started)
+            }
+        }
+    }
+
+    /// Writes the values of the tween at `time_sec` into `values`.
+    fn apply(&mut self, time_sec: f32, values: &mut [(u64, TweenValue)]) {
+        let play_duration: f32 = self.play_duration();
+        let time_sec: f32 = if play_duration <= 0.0 {
+            0.0
+        } else {
+            let time_sec: f32 = time_sec.clamp(0.0, self.duration());
+            let mut play: u32 = (time_sec / play_duration) as u32;
+            let mut local_sec: f32 = time_sec - play as f32 * play_duration;
+            // The end of the last play, rather than the start of the one after it
+            if self.plays.is_some_and(|plays| play >= plays) {
+                play -= 1;
+                local_sec = play_duration;
+            }
+            if self.yoyo && play % 2 == 1 {
+                play_duration - local_sec
+            } else {
+                local_sec
+            }
+        };
+
+        match &mut self.kind {
+            TweenKind::Value {
+                target,
+                from,
+                to,
+                duration_sec,
+                easing,
+                started,
+            } => {
+                let Some((_, value)) = values.iter_mut().find(|(id, _)| id == target) else {
+                    return;
+                };
+                *started = true;
+                let from: TweenValue = *from.get_or_insert(*value);
+                let t: f32 = if *duration_sec <= 0.0 {
+                    1.0
+                } else {
+                    time_sec / *duration_sec
+                };
+                *value = TweenValue::lerp(from, *to, ease(*easing, t));
+            }
+            TweenKind::Delay(_) => {}
+            TweenKind::Sequence(tweens) => {
+                let starts: Vec<f32> = tweens
+                    .iter()
+                    .scan(0.0, |start, tween| {
+                        let tween_start: f32 = *start;
+                        *start += tween.duration();
+                        Some(tween_start)
+                    })
+                    .collect();
+
+                // Later writes win: rewind the upcoming tweens that already started, e.g. when playing backwards,
+                // then finish the past ones in order, then play the current one
+                for (tween, start) in tweens.iter_mut().zip(&starts).rev() {
+                    if time_sec < *start && tween.started() {
+                        tween.apply(0.0, values);
+                    }
+                }
+                for (tween, start) in tweens.iter_mut().zip(&starts) {
+                    if time_sec >= *start {
+                        tween.apply(time_sec - start, values);
+                    }
+                }
+            }
+            TweenKind::Parallel(tweens) => {
+                for tween in tweens {
+                    tween.apply(time_sec, values);
+                }
+            }
+        }
+    }
+}
+
+/// Creates a value that can be animated by tweens, starting at `initial`.
+pub fn create_tweened<T: Tweenable>(engine: &mut Engine, initial: T) -> Tweened<T> {
+    let id: u64 = engine.next_tweened_id;
+    engine.next_tweened_id += 1;
+    engine.tweened_values.push((id, initial.into()));
+    Tweened(id, PhantomData)
+}
+
+/// The current value of a tweened value, or `None` if it was removed.
+pub fn tweened<T: Tweenable>(engine: &Engine, handle: Tweened<T>) -> Option<T> {
+    engine
+        .tweened_values
+        .iter()
+        .find(|(id, _)| *id == handle.0)
+        .and_then(|(_, value)| T::from_tween_value(*value))
+}
+
+/// Sets a tweened value directly. Playing tweens animating it will override it.
+pub fn set_tweened<T: Tweenable>(engine: &mut Engine, handle: Tweened<T>, value: T) {
+    if let Some((_, existing)) = engine
+        .tweened_values
+        .iter_mut()
+        .find(|(id, _)| *id == handle.0)
+    {
+        *existing = value.into();
+    }
+}
+
+/// Removes a tweened value. Tweens animating it keep playing, but have no effect on it.
+pub fn remove_tweened<T: Tweenable>(engine: &mut Engine, handle: Tweened<T>) {
+    engine.tweened_values.retain(|(id, _)| *id != handle.0);
+}
+
+/// Starts playing a tween.
+///
+/// The starting values are applied immediately, so they can be read during the current frame.
+pub fn play_tween(engine: &mut Engine, mut tween: Tween) -> TweenHandle {
+    let handle = TweenHandle(engine.next_tween_id);
+    engine.next_tween_id += 1;
+    tween.apply(0.0, &mut engine.tweened_values);
+    let clock_sec: f32 = if tween.unscaled {
+        engine.real_time
+    } else {
+        engine.game_time
+    };
+    engine.tweens.push(PlayingTween {
+        handle,
+        tween,
+        time_sec: 0.0,
+        clock_sec,
+    });
+    handle
+}
+
+/// Stops a tween, leaving its values as they are.
+pub fn stop_tween(engine: &mut Engine, handle: TweenHandle) {
+    engine.tweens.retain(|playing| playing.handle != handle);
+}
+
+/// Stops all tweens.
+pub fn stop_all_tweens(engine: &mut Engine) {
+    engine.tweens.clear();
+}
+
+/// Whether a tween is still playing.
+pub fn is_tween_playing(engine: &Engine, handle: TweenHandle) -> bool {
+    engine.tweens.iter().any(|playing| playing.handle == handle)
+}
+
+pub(crate) struct PlayingTween {
+    handle: TweenHandle,
+    tween: Tween,
+    time_sec: f32,
+    // The clock the tween follows, as of its last update
+    clock_sec: f32,
+}
+
+/// Advances all playing tweens by as much as their clock moved since their last update,
+/// and removes the finished ones.
+pub(crate) fn update_tweens(engine: &mut Engine) {
+    let (game_time, real_time) = (engine.game_time, engine.real_time);
+    let values: &mut Vec<(u64, TweenValue)> = &mut engine.tweened_values;
+    engine.tweens.retain_mut(|playing| {
+        let clock_sec: f32 = if playing.tween.unscaled {
+            real_time
+        } else {
+            game_time
+        };
+        playing.time_sec += clock_sec - playing.clock_sec;
+        playing.clock_sec = clock_sec;
+        // Keeps the time of looped tweens small, so that they don't lose precision
+        if playing.tween.plays.is_none() {
+            let period: f32 = if playing.tween.yoyo {
+                playing.tween.play_duration() * 2.0
+            } else {
+                playing.tween.play_duration()
+            };
+            if period > 0.0 {
+                playing.time_sec %= period;
+            }
+        }
+        playing.tween.apply(playing.time_sec, values);
+        playing.time_sec < playing.tween.duration()
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn advance(engine: &mut Engine, delta_time: f32) {
+        engine.game_time += delta_time;
+        engine.real_time += delta_time;
+        update_tweens(engine);
+    }
+
+    fn assert_close(actual: Option<f32>, expected: f32) {
+        let actual: f32 = actual.expect("tweened value was removed");
+        assert!(
+            (actual - expected).abs() < 1e-4,
+            "{actual} is not close to {expected}"
+        );
+    }
+
+    #[test]
+    fn sequences_play_steps_one_after_another() {
+        let mut engine = Engine::new(40, 20);
+        let x = create_tweened(&mut engine, 0.0);
+        let handle = play_tween(
+            &mut engine,
+            Tween::sequence([
+                Tween::to(x, 10.0, 1.0, Easing::Linear),
+                Tween::delay(0.5),
+                Tween::to(x, 0.0, 0.5, Easing::Linear),
+            ]),
+        );
+
+        for (delta_time, expected) in [(0.25, 2.5), (0.75, 10.0), (0.25, 10.0), (0.5, 5.0)] {
+            advance(&mut engine, delta_time);
+            assert_close(tweened(&engine, x), expected);
+        }
+        assert!(is_tween_playing(&engine, handle));
+        advance(&mut engine, 0.5);
+        assert_close(tweened(&engine, x), 0.0);
+        assert!(!is_tween_playing(&engine, handle));
+    }
+
+    #[test]
+    fn parallel_tweens_finish_with_the_longest_one() {
+        let mut engine = Engine::new(40, 20);
+        let x = create_tweened(&mut engine, 0.0);
+        let position = create_tweened(&mut engine, (0.0, 0.0));
+        let handle = play_tween(
+            &mut engine,
+            Tween::parallel([
+                Tween::to(x, 1.0, 0.5, Easing::Linear),
+                Tween::to(position, (4.0, 8.0), 1.0, Easing::Linear),
+            ]),
+        );
+
+        advance(&mut engine, 0.5);
+        assert_close(tweened(&engine, x), 1.0);
+        assert_eq!(tweened(&engine, position), Some((2.0, 4.0)));
+        advance(&mut engine, 0.25);
+        assert!(is_tween_playing(&engine, handle));
+        advance(&mut engine, 0.25);
+        assert_eq!(tweened(&engine, position), Some((4.0, 8.0)));
+        assert!(!is_tween_playing(&engine, handle));
+    }
+
+    #[test]
+    fn yoyo_plays_every_other_repetition_backwards() {
+        let mut engine = Engine::new(40, 20);
+        let x = create_tweened(&mut engine, 0.0);
+        let handle = play_tween(
+            &mut engine,
+            Tween::from_to(x, 0.0, 10.0, 1.0, Easing::Linear)
+                .repeat(3)
+                .yoyo(true),
+        );
+
+        for (delta_time, expected) in [(0.5, 5.0), (0.75, 7.5), (0.5, 2.5), (0.5, 2.5)] {
+            advance(&mut engine, delta_time);
+            assert_close(tweened(&engine, x), expected);
+        }
+        advance(&mut engine, 1.0);
+        assert_close(tweened(&engine, x), 10.0);
+        assert!(!is_tween_playing(&engine, handle));
+    }
+
+    #[test]
+    fn yoyo_sequences_rewind_their_steps() {
+        let mut engine = Engine::new(40, 20);
+        let x = create_tweened(&mut engine, 0.0);
+        let y = create_tweened(&mut engine, 0.0);
+        play_tween(
+            &mut engine,
+            Tween::sequence([
+                Tween::to(x, 10.0, 1.0, Easing::Linear),
+                Tween::to(y, 10.0, 1.0, Easing::Linear),
+            ])
+            .looped()
+            .yoyo(true),
+        );
+
+        advance(&mut engine, 2.0);
+        assert_eq!(
+            (tweened(&engine, x), tweened(&engine, y)),
+            (Some(10.0), Some(10.0))
+        );
+        advance(&mut engine, 1.5);
+        assert_close(tweened(&engine, x), 5.0);
+        assert_close(tweened(&engine, y), 0.0);
+        advance(&mut engine, 0.5);
+        assert_close(tweened(&engine, x), 0.0);
+    }
+
+    #[test]
+    fn looped_tweens_keep_playing_until_stopped() {
+        let mut engine = Engine::new(40, 20);
+        let x = create_tweened(&mut engine, 0.0);
+        let handle = play_tween(
+            &mut engine,
+            Tween::from_to(x, 0.0, 10.0, 1.0, Easing::Linear).looped(),
+        );
+
+        for _ in 0..100 {
+            advance(&mut engine, 0.1);
+        }
+        assert!(is_tween_playing(&engine, handle));
+        advance(&mut engine, 0.25);
+        assert_close(tweened(&engine, x), 2.5);
+
+        stop_tween(&mut engine, handle);
+        advance(&mut engine, 0.25);
+        assert!(!is_tween_playing(&engine, handle));
+        assert_close(tweened(&engine, x), 2.5);
+    }
+
+    #[test]
+    fn unscaled_tweens_ignore_pausing() {
+        let mut engine = Engine::new(40, 20);
+        let x = create_tweened(&mut engine, 0.0);
+        let y = create_tweened(&mut engine, 0.0);
+        play_tween(&mut engine, Tween::to(x, 1.0, 1.0, Easing::Linear));
+        play_tween(
+            &mut engine,
+            Tween::to(y, 1.0, 1.0, Easing::Linear).unscaled(true),
+        );
+
+        // Paused game time stands still
+        engine.real_time += 0.5;
+        update_tweens(&mut engine);
+        assert_close(tweened(&engine, x), 0.0);
+        assert_close(tweened(&engine, y), 0.5);
+    }
+
+    #[test]
+    fn upcoming_from_to_steps_wait_for_their_turn() {
+        let mut engine = Engine::new(40, 20);
+        let x = create_tweened(&mut engine, 0.0);
+        play_tween(
+            &mut engine,
+            Tween::sequence([
+                Tween::to(x, 10.0, 1.0, Easing::Linear),
+                Tween::from_to(x, 100.0, 200.0, 1.0, Easing::Linear),
+            ]),
+        );
+        assert_close(tweened(&engine, x), 0.0);
+
+        advance(&mut engine, 0.5);
+        assert_close(tweened(&engine, x), 5.0);
+
+        advance(&mut engine, 1.0);
+        assert_close(tweened(&engine, x), 150.0);
+    }
+}